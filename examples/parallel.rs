@@ -0,0 +1,75 @@
+// Times `join`/`reduce` against `par_join`/`par_reduce` on the same input:
+//
+//     cargo run --release --example parallel --features parallel
+//
+// The rayon pool uses one thread per core (`RAYON_NUM_THREADS` overrides it), so
+// the parallel operators can only come out ahead on a multi-core machine.
+
+use std::time::{Duration, Instant};
+
+use standing_relations_2::{CreationContext, Output};
+
+const KEYS: u64 = 2_000;
+const PER_KEY: u64 = 50;
+const ROUNDS: u64 = 5;
+
+// Stands in for a reduce function that does real work per value.
+fn score(x: u64) -> u64 {
+    (0..200).fold(x, |acc, i| {
+        acc.wrapping_mul(6364136223846793005).wrapping_add(i)
+    })
+}
+
+fn run(parallel: bool) -> (Duration, usize) {
+    let mut context = CreationContext::new();
+    let (mut left_input, left) = context.input::<(u64, u64)>();
+    let (mut right_input, right) = context.input::<(u64, u64)>();
+    let left = left.save();
+    let (joined, reduced): (Output<_>, Output<_>) = if parallel {
+        (
+            context.output(left.get().par_join(right).dynamic()),
+            context.output(
+                left.get()
+                    .par_reduce(|_, vals| vals.into_iter().map(|(&v, _)| score(v)).sum::<u64>())
+                    .dynamic(),
+            ),
+        )
+    } else {
+        (
+            context.output(left.get().join(right).dynamic()),
+            context.output(
+                left.get()
+                    .reduce(|_, vals| vals.into_iter().map(|(&v, _)| score(v)).sum::<u64>())
+                    .dynamic(),
+            ),
+        )
+    };
+    let mut context = context.begin();
+
+    let mut elapsed = Duration::ZERO;
+    let mut len = 0;
+    for round in 0..ROUNDS {
+        for k in 0..KEYS {
+            for i in 0..PER_KEY {
+                left_input.send((k, round * PER_KEY + i)).unwrap();
+            }
+            right_input.send((k, round)).unwrap();
+        }
+        // Outputs are pulled when read, so the reads are timed along with the commit.
+        let start = Instant::now();
+        context.commit().unwrap();
+        len = joined.get().len() + reduced.get().len();
+        elapsed += start.elapsed();
+    }
+    (elapsed, len)
+}
+
+fn main() {
+    let threads = rayon::current_num_threads();
+    let (sequential, expected) = run(false);
+    let (parallel, len) = run(true);
+    assert_eq!(len, expected);
+    println!("{} rayon threads", threads);
+    println!("join + reduce:         {:?}", sequential);
+    println!("par_join + par_reduce: {:?}", parallel);
+}
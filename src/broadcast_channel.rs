@@ -1,12 +1,17 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, hash::Hash};
 
 use derivative::Derivative;
 
-use crate::channel;
+use crate::{channel, value_count::ValueCount};
+
+// A queue is merged once it holds twice as many changes as after its last merge (and
+// at least this many), so a receiver that goes unread for many commits holds about as
+// many changes as it has distinct values.
+const MIN_CONSOLIDATE_LEN: usize = 64;
 
 #[derive(Derivative)]
 #[derivative(Default(bound = ""))]
-pub struct Sender<T>(RefCell<Vec<channel::Sender<T>>>);
+pub struct Sender<T>(RefCell<Vec<(channel::Sender<T>, usize)>>);
 
 pub type Receiver<T> = channel::Receiver<T>;
 
@@ -16,7 +21,7 @@ impl<T> Sender<T> {
     }
     pub fn subscribe(&mut self) -> Receiver<T> {
         let (sender, receiver) = channel::new();
-        self.0.borrow_mut().push(sender);
+        self.0.borrow_mut().push((sender, 0));
         receiver
     }
     pub fn send(&mut self, value: &T)
@@ -25,6 +30,16 @@ impl<T> Sender<T> {
     {
         self.0
             .get_mut()
-            .retain_mut(|sender| sender.send(value.clone()).is_ok())
+            .retain_mut(|(sender, _)| sender.send(value.clone()).is_ok())
+    }
+}
+
+impl<T: Eq + Hash> Sender<(T, ValueCount)> {
+    pub fn consolidate(&mut self) {
+        for (sender, merged_len) in self.0.get_mut().iter_mut() {
+            if sender.len() >= 2 * (*merged_len).max(MIN_CONSOLIDATE_LEN) {
+                *merged_len = sender.consolidate();
+            }
+        }
     }
 }
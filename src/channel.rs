@@ -1,11 +1,14 @@
 use std::{
     cell::RefCell,
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
+    hash::Hash,
     rc::{Rc, Weak},
 };
 
 use derivative::Derivative;
 
+use crate::{generic_map::AddMap, value_count::ValueCount};
+
 #[derive(Derivative)]
 #[derivative(Clone(bound = ""))]
 pub struct Sender<T>(Weak<RefCell<VecDeque<T>>>);
@@ -18,6 +21,9 @@ pub fn new<T>() -> (Sender<T>, Receiver<T>) {
 }
 
 impl<T> Sender<T> {
+    pub fn is_connected(&self) -> bool {
+        self.0.strong_count() > 0
+    }
     pub fn send(&mut self, value: T) -> Result<(), T> {
         match self.0.upgrade() {
             Some(this) => {
@@ -29,6 +35,25 @@ impl<T> Sender<T> {
     }
 }
 
+impl<T: Eq + Hash> Sender<(T, ValueCount)> {
+    pub fn len(&self) -> usize {
+        self.0.upgrade().map_or(0, |this| this.borrow().len())
+    }
+    // Merges queued changes to the same value and returns the new queue length.
+    pub fn consolidate(&mut self) -> usize {
+        let Some(this) = self.0.upgrade() else {
+            return 0;
+        };
+        let mut queue = this.borrow_mut();
+        let mut counts = HashMap::new();
+        for change in queue.drain(..) {
+            counts.add(change);
+        }
+        queue.extend(counts);
+        queue.len()
+    }
+}
+
 impl<T> Receiver<T> {
     pub fn try_recv(&mut self) -> Option<T> {
         self.0.borrow_mut().pop_front()
@@ -1,50 +1,91 @@
 use std::cell::Cell;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 #[cfg(feature = "redis")]
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Instant;
 
 use index_list::IndexList;
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
 use uuid::Uuid;
 
 use crate::{
     arc_key::ArcKey,
     channel,
+    cycles::{CycleCheck, CycleError, FeedbackCycle},
+    graph::{representative, DataflowGraph, SinkData, SinkKind},
     op::Op,
-    operators::input::{Input, InputOp},
+    operators::{
+        input::{Input, InputOp},
+        save::{Saved, SavedOp},
+        variable::{Variable, VariableFeed},
+    },
     output::Output,
+    profile::Profile,
     relation::{data::RelationData, Relation},
+    sink::{Callback, Sink},
     value_count::ValueCount,
     who::Who,
 };
 
+#[cfg(feature = "serde")]
+use crate::snapshot::{self, Snapshot, SnapshotError};
+
+#[cfg(feature = "serde")]
+use self::pipes::persisted::Persisted;
 #[cfg(feature = "redis")]
 use self::pipes::redis::RedisPipe;
+#[cfg(feature = "redis")]
+pub use self::pipes::redis::{RedisEncoding, RedisLayout, RedisRetry};
 use self::pipes::{
-    feedback::FeedbackPipe, interrupt::Interrupt, tracked::TrackedInputPipe,
-    untracked::UntrackedInputPipe, PipeT, ProcessResult, Processable,
+    bag::BagInputPipe, constraint::Constraint, feedback::FeedbackPipe, interrupt::Interrupt,
+    sink::SinkPipe, tracked::TrackedInputPipe, untracked::UntrackedInputPipe, PipeT, ProcessResult,
+    Processable,
 };
+use self::schedule::Worklist;
 
-pub use self::pipes::interrupt::InterruptId;
+pub use self::commit::{CommitError, CommitLimits, PipeInfo, TransactionError, ViolatingTuples};
+pub use self::pipes::constraint::ConstraintHandle;
+pub use self::pipes::interrupt::{InterruptHandle, InterruptId};
+pub use self::schedule::Scheduler;
 
+mod commit;
 mod pipes;
+mod schedule;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub(crate) struct ContextId(Uuid);
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct InputId(usize);
+
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default, PartialOrd, Ord)]
 pub struct CommitId(usize);
 
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord)]
+pub struct FrameId {
+    depth: usize,
+    generation: usize,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct SinkId(usize);
+
 pub struct CreationContext<'a> {
     id: ContextId,
     commit_id: Rc<Cell<CommitId>>,
-    input_pipes: Vec<Box<dyn PipeT + 'a>>,
-    feedback_pipes: IndexList<Box<dyn Processable + 'a>>,
+    input_pipes: Vec<(InputId, Box<dyn PipeT + 'a>)>,
+    feedback_pipes: IndexList<(usize, Box<dyn Processable + 'a>)>,
     relational_graph: HashSet<ArcKey<RelationData>>,
+    sinks: Vec<SinkData>,
+    cycle_check: CycleCheck,
     #[cfg(feature = "redis")]
     redis: Option<redis::Client>,
+    #[cfg(feature = "redis")]
+    redis_retry: RedisRetry,
 }
 
 impl<'a> Default for CreationContext<'a> {
@@ -61,8 +102,12 @@ impl<'a> CreationContext<'a> {
             input_pipes: Vec::new(),
             feedback_pipes: IndexList::new(),
             relational_graph: HashSet::new(),
+            sinks: Vec::new(),
+            cycle_check: CycleCheck::default(),
             #[cfg(feature = "redis")]
             redis: None,
+            #[cfg(feature = "redis")]
+            redis_retry: RedisRetry::default(),
         }
     }
     #[cfg(feature = "redis")]
@@ -72,27 +117,47 @@ impl<'a> CreationContext<'a> {
             ..Self::new()
         }
     }
+    #[cfg(feature = "redis")]
+    pub fn set_redis_retry(&mut self, retry: RedisRetry) {
+        self.redis_retry = retry;
+    }
+    #[track_caller]
     pub fn input<T: Eq + Hash + Clone + 'a>(&mut self) -> (Input<T>, Relation<T, InputOp<T>>) {
-        let (sender1, receiver1) = channel::new::<(T, Who)>();
-        let (sender2, receiver2) = channel::new::<(T, ValueCount)>();
-        self.input_pipes
-            .push(Box::new(TrackedInputPipe::new(receiver1, sender2)));
-        (
-            Input::new(self.id, sender1),
-            Relation::from_op(self.id, move |()| InputOp::new(receiver2)),
-        )
+        self.new_input(TrackedInputPipe::new)
     }
+    #[track_caller]
     pub fn frameless_input<T: Eq + Hash + Clone + 'a>(
         &mut self,
     ) -> (Input<T>, Relation<T, InputOp<T>>) {
-        let (sender1, receiver1) = channel::new::<(T, Who)>();
-        let (sender2, receiver2) = channel::new::<(T, ValueCount)>();
-        self.input_pipes
-            .push(Box::new(UntrackedInputPipe::new(receiver1, sender2)));
-        (
-            Input::new(self.id, sender1),
-            Relation::from_op(self.id, move |()| InputOp::new(receiver2)),
-        )
+        self.new_input(UntrackedInputPipe::new)
+    }
+    #[track_caller]
+    pub fn bag_input<T: Eq + Hash + Clone + 'a>(&mut self) -> (Input<T>, Relation<T, InputOp<T>>) {
+        self.new_input(BagInputPipe::new)
+    }
+    #[cfg(feature = "serde")]
+    #[track_caller]
+    pub fn persisted_input<T>(&mut self) -> (Input<T>, Relation<T, InputOp<T>>)
+    where
+        T: Eq + Hash + Clone + Serialize + DeserializeOwned + 'a,
+    {
+        self.new_input(|receiver, sender| Persisted(TrackedInputPipe::new(receiver, sender)))
+    }
+    #[cfg(feature = "serde")]
+    #[track_caller]
+    pub fn persisted_frameless_input<T>(&mut self) -> (Input<T>, Relation<T, InputOp<T>>)
+    where
+        T: Eq + Hash + Clone + Serialize + DeserializeOwned + 'a,
+    {
+        self.new_input(|receiver, sender| Persisted(UntrackedInputPipe::new(receiver, sender)))
+    }
+    #[cfg(feature = "serde")]
+    #[track_caller]
+    pub fn persisted_bag_input<T>(&mut self) -> (Input<T>, Relation<T, InputOp<T>>)
+    where
+        T: Eq + Hash + Clone + Serialize + DeserializeOwned + 'a,
+    {
+        self.new_input(|receiver, sender| Persisted(BagInputPipe::new(receiver, sender)))
     }
     pub fn feedback<T: Eq + Hash + Clone + 'a>(
         &mut self,
@@ -101,52 +166,273 @@ impl<'a> CreationContext<'a> {
     ) {
         assert_eq!(self.id, relation.context_id);
         assert_eq!(self.id, input.context_id);
-        self.add_all(&Arc::new(relation.data));
-        self.feedback_pipes
-            .insert_last(Box::new(FeedbackPipe::new(relation.inner, input)));
+        self.add_feedback_pipe(
+            SinkKind::Feedback(input.input_id),
+            relation.data,
+            FeedbackPipe::new(relation.inner, input),
+        );
+    }
+    #[track_caller]
+    pub fn variable<T: Eq + Hash + Clone + 'a>(&mut self) -> (Variable<T>, VariableFeed<T>) {
+        let (input, relation) = self.input();
+        (
+            Variable {
+                saved: relation.type_named("variable").save(),
+            },
+            VariableFeed { input },
+        )
+    }
+    pub fn feed<T: Eq + Hash + Clone + 'a>(
+        &mut self,
+        feed: VariableFeed<T>,
+        relation: Relation<T, impl Op<T> + 'a>,
+    ) {
+        self.feedback(relation, feed.input)
+    }
+    #[track_caller]
+    pub fn iterate<T: Eq + Hash + Clone + 'a, C: Op<T> + 'a>(
+        &mut self,
+        initial: Relation<T, impl Op<T> + 'a>,
+        body: impl FnOnce(Relation<T, SavedOp<T, InputOp<T>>>) -> Relation<T, C>,
+    ) -> Saved<T, InputOp<T>> {
+        let (variable, feed) = self.variable();
+        let next = body(variable.get());
+        self.feed(feed, initial.concat(next));
+        variable.into_saved()
     }
-    pub fn interrupt<T: Eq + Hash + 'a, C: Op<T> + 'a>(
+    /// The handle keeps both the relation's contents and the delta of the last commit,
+    /// which is why `T` must be `Clone`.
+    pub fn interrupt<T: Eq + Hash + Clone + 'a, C: Op<T> + 'a>(
         &mut self,
         id: InterruptId,
         relation: Relation<T, C>,
-    ) {
+    ) -> InterruptHandle<T> {
         assert_eq!(self.id, relation.context_id);
-        self.add_all(&Arc::new(relation.data));
-        self.feedback_pipes
-            .insert_last(Box::new(Interrupt::new(id, relation.inner)));
+        let (interrupt, handle) = Interrupt::new(id, relation.inner);
+        self.add_feedback_pipe(SinkKind::Interrupt(id), relation.data, interrupt);
+        handle
+    }
+    pub fn constraint<T: Eq + Hash + Clone + 'static, C: Op<T> + 'a>(
+        &mut self,
+        name: impl Into<String>,
+        relation: Relation<T, C>,
+    ) -> ConstraintHandle<T> {
+        assert_eq!(self.id, relation.context_id);
+        let name = name.into();
+        let (constraint, handle) = Constraint::new(name.clone(), relation.inner);
+        self.add_feedback_pipe(SinkKind::Constraint(name), relation.data, constraint);
+        handle
+    }
+    #[track_caller]
+    pub fn functional_dependency<K, V>(
+        &mut self,
+        name: impl Into<String>,
+        relation: Relation<(K, V), impl Op<(K, V)> + 'a>,
+    ) -> ConstraintHandle<(K, V)>
+    where
+        K: Eq + Hash + Clone + 'static,
+        V: Eq + Hash + Clone + 'static,
+    {
+        let pairs = relation.distinct().save();
+        let conflicting_keys = pairs
+            .get()
+            .fsts()
+            .counts()
+            .filter(|&(_, count)| count > 1)
+            .fsts();
+        let violations = pairs
+            .get()
+            .semijoin(conflicting_keys)
+            .type_named("functional_dependency");
+        self.constraint(name, violations)
+    }
+    #[track_caller]
+    pub fn foreign_key<K: Eq + Hash + Clone + 'static>(
+        &mut self,
+        name: impl Into<String>,
+        references: Relation<K, impl Op<K> + 'a>,
+        keys: Relation<K, impl Op<K> + 'a>,
+    ) -> ConstraintHandle<K> {
+        let violations = references
+            .distinct()
+            .map_h(|k| (k, ()))
+            .antijoin(keys)
+            .fsts()
+            .type_named("foreign_key");
+        self.constraint(name, violations)
     }
     pub fn output<T, C>(&mut self, relation: Relation<T, C>) -> Output<T, C> {
         assert_eq!(self.id, relation.context_id);
-        self.add_all(&Arc::new(relation.data));
+        self.add_sink(SinkKind::Output, relation.data);
         Output::new(relation.inner, self.commit_id.clone())
     }
+    /// Calls `f` once per commit, after the fixpoint is reached, with the consolidated
+    /// changes since the previous call.
+    pub fn subscribe<T: Eq + Hash + 'a, C: Op<T> + 'a>(
+        &mut self,
+        relation: Relation<T, C>,
+        f: impl FnMut(&HashMap<T, ValueCount>) + 'a,
+    ) -> SinkId {
+        assert_eq!(self.id, relation.context_id);
+        self.add_feedback_pipe(
+            SinkKind::Subscription,
+            relation.data,
+            SinkPipe::new(relation.inner, Callback(f)),
+        )
+    }
+    pub fn sink<T: Eq + Hash + 'a, C: Op<T> + 'a>(
+        &mut self,
+        relation: Relation<T, C>,
+        sink: impl Sink<T> + 'a,
+    ) -> SinkId {
+        assert_eq!(self.id, relation.context_id);
+        self.add_feedback_pipe(
+            SinkKind::Sink,
+            relation.data,
+            SinkPipe::new(relation.inner, sink),
+        )
+    }
     #[cfg(feature = "redis")]
-    pub fn send_to_redis<T, C>(&mut self, name: impl ToString, relation: Relation<T, C>)
+    pub fn send_to_redis<T, C>(&mut self, name: impl ToString, relation: Relation<T, C>) -> SinkId
     where
         T: Clone + Eq + Hash + Debug + 'a,
         C: Op<T> + 'a,
+    {
+        self.send_to_redis_with(name, relation, RedisEncoding::debug())
+    }
+    #[cfg(feature = "redis")]
+    pub fn send_to_redis_with<T, C>(
+        &mut self,
+        name: impl ToString,
+        relation: Relation<T, C>,
+        encoding: RedisEncoding<'a, T>,
+    ) -> SinkId
+    where
+        T: Clone + Eq + Hash + 'a,
+        C: Op<T> + 'a,
+    {
+        self.send_to_redis_as(name, relation, RedisLayout::Keys, encoding)
+    }
+    #[cfg(feature = "redis")]
+    pub fn send_to_redis_as<T, C>(
+        &mut self,
+        name: impl ToString,
+        relation: Relation<T, C>,
+        layout: RedisLayout<'a, T>,
+        encoding: RedisEncoding<'a, T>,
+    ) -> SinkId
+    where
+        T: Clone + Eq + Hash + 'a,
+        C: Op<T> + 'a,
     {
         assert_eq!(self.id, relation.context_id);
-        self.add_all(&Arc::new(relation.data));
-        self.feedback_pipes.insert_last(Box::new(RedisPipe::new(
-            name.to_string(),
+        let name = name.to_string();
+        let pipe = RedisPipe::new(
+            name.clone(),
             relation.inner,
+            encoding,
+            layout,
             self.redis.clone().unwrap(),
-        )));
+            self.redis_retry,
+        );
+        self.add_feedback_pipe(SinkKind::Redis(name), relation.data, pipe)
+    }
+    pub fn dataflow_graph(&self) -> DataflowGraph {
+        DataflowGraph::new(&self.sinks)
+    }
+    pub fn set_cycle_check(&mut self, cycle_check: CycleCheck) {
+        self.cycle_check = cycle_check;
+    }
+    pub fn feedback_cycles(&self) -> Vec<FeedbackCycle> {
+        FeedbackCycle::find_all(&self.sinks)
     }
     pub fn begin(self) -> ExecutionContext<'a> {
+        match self.try_begin() {
+            Ok(context) => context,
+            Err(err) => panic!("{}", err),
+        }
+    }
+    pub fn try_begin(self) -> Result<ExecutionContext<'a>, CycleError> {
+        match self.cycle_check {
+            CycleCheck::Ignore => {}
+            CycleCheck::Warn => {
+                for cycle in self.feedback_cycles() {
+                    #[cfg(feature = "log")]
+                    log::warn!("{}", cycle);
+                    #[cfg(not(feature = "log"))]
+                    eprintln!("warning: {}", cycle);
+                }
+            }
+            CycleCheck::Error => {
+                let cycles = self.feedback_cycles();
+                if !cycles.is_empty() {
+                    return Err(CycleError(cycles));
+                }
+            }
+        }
         let Self {
             id: _,
             commit_id,
             input_pipes,
             feedback_pipes,
+            relational_graph,
+            sinks,
             ..
         } = self;
-        ExecutionContext {
+        let sink_inputs = schedule::sink_inputs(&sinks);
+        let dependents =
+            schedule::dependents(&sink_inputs, feedback_pipes.iter().map(|&(sink, _)| sink));
+        Ok(ExecutionContext {
+            id: self.id,
             commit_id,
             input_pipes,
             feedback_pipes,
-        }
+            relational_graph,
+            sinks,
+            frames: Vec::new(),
+            frame_generation: 0,
+            scheduler: Scheduler::default(),
+            sink_inputs,
+            dependents,
+            inactive: HashSet::new(),
+            suspended: HashSet::new(),
+            #[cfg(feature = "serde")]
+            interrupted: false,
+        })
+    }
+
+    #[track_caller]
+    fn new_input<T: 'a, P: PipeT + 'a>(
+        &mut self,
+        pipe: impl FnOnce(channel::Receiver<(T, Who)>, channel::Sender<(T, ValueCount)>) -> P,
+    ) -> (Input<T>, Relation<T, InputOp<T>>) {
+        let (sender1, receiver1) = channel::new::<(T, Who)>();
+        let (sender2, receiver2) = channel::new::<(T, ValueCount)>();
+        let input_id = InputId(self.input_pipes.len());
+        let pipe = pipe(receiver1, sender2);
+        let frameless = pipe.is_frameless();
+        self.input_pipes.push((input_id, Box::new(pipe)));
+        let mut relation = Relation::from_op(self.id, move |()| InputOp::new(receiver2));
+        relation.data.input_id = Some(input_id);
+        (Input::new(self.id, input_id, frameless, sender1), relation)
+    }
+
+    fn add_sink(&mut self, kind: SinkKind, data: RelationData) -> usize {
+        let relation = Arc::new(data);
+        self.add_all(&relation);
+        self.sinks.push(SinkData { kind, relation });
+        self.sinks.len() - 1
+    }
+
+    fn add_feedback_pipe(
+        &mut self,
+        kind: SinkKind,
+        data: RelationData,
+        pipe: impl Processable + 'a,
+    ) -> SinkId {
+        let sink = self.add_sink(kind, data);
+        self.feedback_pipes.insert_last((sink, Box::new(pipe)));
+        SinkId(sink)
     }
 
     fn add_all(&mut self, data: &Arc<RelationData>) {
@@ -159,77 +445,404 @@ impl<'a> CreationContext<'a> {
 }
 
 pub struct ExecutionContext<'a> {
+    id: ContextId,
     commit_id: Rc<Cell<CommitId>>,
-    input_pipes: Vec<Box<dyn PipeT + 'a>>,
-    feedback_pipes: IndexList<Box<dyn Processable + 'a>>,
+    input_pipes: Vec<(InputId, Box<dyn PipeT + 'a>)>,
+    feedback_pipes: IndexList<(usize, Box<dyn Processable + 'a>)>,
+    relational_graph: HashSet<ArcKey<RelationData>>,
+    sinks: Vec<SinkData>,
+    // The generation of each open frame, so a stale `FrameId` is not mistaken for a
+    // newer frame at the same depth.
+    frames: Vec<usize>,
+    frame_generation: usize,
+    scheduler: Scheduler,
+    sink_inputs: Vec<Vec<InputId>>,
+    dependents: HashMap<InputId, Vec<usize>>,
+    inactive: HashSet<usize>,
+    suspended: HashSet<usize>,
+    #[cfg(feature = "serde")]
+    interrupted: bool,
 }
 
 impl ExecutionContext<'_> {
-    pub fn commit(&mut self) -> Result<(), InterruptId> {
+    pub fn commit(&mut self) -> Result<(), CommitError> {
+        self.commit_with_limits(CommitLimits::default())
+    }
+
+    pub fn commit_with_limits(&mut self, limits: CommitLimits) -> Result<(), CommitError> {
+        let result = self.commit_inner(&limits);
+        #[cfg(feature = "serde")]
+        {
+            self.interrupted = result.is_err();
+        }
+        result
+    }
+
+    fn commit_inner(&mut self, limits: &CommitLimits) -> Result<(), CommitError> {
+        for data in self.relational_graph.iter() {
+            data.stats.start_commit();
+        }
+        let mut worklist = Worklist::new(self.scheduler);
+        let mut indices = HashMap::new();
+        let mut i = self.feedback_pipes.first_index();
+        while i.is_some() {
+            let (sink, pipe) = self.feedback_pipes.get_mut(i).unwrap();
+            pipe.start_commit();
+            if !self.suspended.contains(sink) {
+                worklist.push(*sink);
+            }
+            indices.insert(*sink, i);
+            i = self.feedback_pipes.next_index(i);
+        }
         self.one_pass();
-        'outer: loop {
-            let commit_id = self.commit_id.get();
-            let mut i = self.feedback_pipes.first_index();
-            while i.is_some() {
-                let next_i = self.feedback_pipes.next_index(i);
-                match self.feedback_pipes.get_mut(i).unwrap().process(commit_id) {
-                    Ok(ProcessResult::Changed) => {
-                        self.one_pass();
-                        continue 'outer;
+        let mut rounds = 0;
+        let mut seen_deltas = HashSet::new();
+        while let Some(sink) = worklist.pop() {
+            let Some(&i) = indices.get(&sink) else {
+                continue;
+            };
+            let (_, pipe) = self.feedback_pipes.get_mut(i).unwrap();
+            match pipe.process(self.commit_id.get()) {
+                Ok(ProcessResult::Changed) => {
+                    let repeated = limits.detect_oscillation
+                        && pipe
+                            .delta_hash()
+                            .is_some_and(|hash| !seen_deltas.insert((sink, hash)));
+                    for input_id in self.one_pass_changed() {
+                        for &dependent in self.dependents.get(&input_id).into_iter().flatten() {
+                            if !self.suspended.contains(&dependent) {
+                                worklist.push(dependent);
+                            }
+                        }
+                    }
+                    rounds += 1;
+                    if repeated {
+                        let pipe = self.pipe_info(sink);
+                        return Err(CommitError::Oscillation { rounds, pipe });
                     }
-                    Ok(ProcessResult::Unchanged) => {}
-                    Ok(ProcessResult::Interrupted(interrupt_id)) => {
-                        return Err(interrupt_id);
+                    if limits.max_rounds.is_some_and(|max| rounds >= max) {
+                        let pipe = self.pipe_info(sink);
+                        return Err(CommitError::RoundLimit { rounds, pipe });
                     }
-                    Err(Dropped) => {
-                        self.feedback_pipes.remove(i);
+                    if limits
+                        .deadline
+                        .is_some_and(|deadline| Instant::now() >= deadline)
+                    {
+                        let pipe = self.pipe_info(sink);
+                        return Err(CommitError::Deadline { rounds, pipe });
                     }
                 }
-                i = next_i;
+                Ok(ProcessResult::Unchanged) => {}
+                Ok(ProcessResult::Interrupted(interrupt_id)) => {
+                    return Err(CommitError::Interrupted(interrupt_id));
+                }
+                Ok(ProcessResult::Violated(name, tuples)) => {
+                    return Err(CommitError::ConstraintViolation { name, tuples });
+                }
+                Ok(ProcessResult::Failed(message)) => {
+                    let pipe = self.pipe_info(sink);
+                    return Err(CommitError::SinkFailed { pipe, message });
+                }
+                Err(Dropped) => {
+                    self.feedback_pipes.remove(i);
+                    indices.remove(&sink);
+                }
+            }
+        }
+        // Every sink gets this commit's changes, whichever one fails first.
+        let mut result = Ok(());
+        let mut i = self.feedback_pipes.first_index();
+        while i.is_some() {
+            let (sink, pipe) = self.feedback_pipes.get_mut(i).unwrap();
+            let sink = *sink;
+            if !self.suspended.contains(&sink) {
+                let err = match pipe.finish() {
+                    ProcessResult::Interrupted(interrupt_id) => {
+                        Some(CommitError::Interrupted(interrupt_id))
+                    }
+                    ProcessResult::Failed(message) => {
+                        let pipe = self.pipe_info(sink);
+                        Some(CommitError::SinkFailed { pipe, message })
+                    }
+                    _ => None,
+                };
+                if let (Ok(()), Some(err)) = (&result, err) {
+                    result = Err(err);
+                }
+            }
+            i = self.feedback_pipes.next_index(i);
+        }
+        result
+    }
+
+    /// Removes what sinks have written to external stores, as dropping the context
+    /// does, but retries and returns the first failure instead of logging it.
+    pub fn close(mut self) -> Result<(), CommitError> {
+        let mut result = Ok(());
+        let mut i = self.feedback_pipes.first_index();
+        while i.is_some() {
+            let (sink, pipe) = self.feedback_pipes.get_mut(i).unwrap();
+            let sink = *sink;
+            if let (Ok(()), ProcessResult::Failed(message)) = (&result, pipe.close()) {
+                let pipe = self.pipe_info(sink);
+                result = Err(CommitError::SinkFailed { pipe, message });
             }
-            return Ok(());
+            i = self.feedback_pipes.next_index(i);
+        }
+        result
+    }
+
+    pub(crate) fn owns<T>(&self, input: &Input<T>) -> bool {
+        input.context_id == self.id
+    }
+
+    pub fn set_scheduler(&mut self, scheduler: Scheduler) {
+        self.scheduler = scheduler;
+    }
+
+    /// Suspends or resumes a sink. While inactive, the sink and any feedback only it
+    /// demands are skipped by `commit`, and `catch_up` brings it up to date before it
+    /// is read. Resuming it with `set_active(sink, true)` catches it up on the next
+    /// commit instead. `Output`s are pulled on read and cannot be suspended.
+    pub fn set_active(&mut self, sink: SinkId, active: bool) {
+        if active {
+            self.inactive.remove(&sink.0);
+        } else {
+            self.inactive.insert(sink.0);
+        }
+        self.suspended = schedule::suspended(&self.sinks, &self.sink_inputs, &self.inactive);
+    }
+
+    pub fn is_active(&self, sink: SinkId) -> bool {
+        !self.inactive.contains(&sink.0)
+    }
+
+    /// Commits with `sink` active, delivering everything it missed while suspended,
+    /// and then suspends it again.
+    pub fn catch_up(&mut self, sink: SinkId) -> Result<(), CommitError> {
+        if self.is_active(sink) {
+            return self.commit();
         }
+        self.set_active(sink, true);
+        let result = self.commit();
+        self.set_active(sink, false);
+        result
     }
 
     pub fn with_frame<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
-        self.one_pass();
+        let frame_id = self.push_frame();
+        let result = f(self);
+        self.rollback_to(frame_id);
+        result
+    }
 
-        for input in self.input_pipes.iter_mut() {
+    pub fn push_frame(&mut self) -> FrameId {
+        self.one_pass();
+        for (_, input) in self.input_pipes.iter_mut() {
             input.push_frame();
         }
+        let frame_id = FrameId {
+            depth: self.frames.len(),
+            generation: self.frame_generation,
+        };
+        self.frames.push(self.frame_generation);
+        self.frame_generation += 1;
+        frame_id
+    }
 
-        let result = f(self);
+    pub fn pop_frame(&mut self) {
+        let generation = *self.frames.last().expect("no frame to pop");
+        self.rollback_to(FrameId {
+            depth: self.frames.len() - 1,
+            generation,
+        })
+    }
 
-        self.input_pipes
-            .retain_mut(|input| input.pop_frame(self.commit_id.get()).is_ok());
+    pub fn rollback_to(&mut self, frame_id: FrameId) {
+        assert_eq!(
+            self.frames.get(frame_id.depth),
+            Some(&frame_id.generation),
+            "frame already popped"
+        );
+        while self.frames.len() > frame_id.depth {
+            self.frames.pop();
+            self.input_pipes
+                .retain_mut(|(_, input)| input.pop_frame(self.commit_id.get()).is_ok());
+        }
 
         self.commit_id.set(CommitId(self.commit_id.get().0 + 1));
         let mut i = self.feedback_pipes.first_index();
         while i.is_some() {
             let next_i = self.feedback_pipes.next_index(i);
-            match self
-                .feedback_pipes
-                .get_mut(i)
-                .unwrap()
-                .process(self.commit_id.get())
-            {
-                Ok(_) => {}
-                Err(Dropped) => {
-                    self.feedback_pipes.remove(i);
-                }
+            let (sink, pipe) = self.feedback_pipes.get_mut(i).unwrap();
+            if self.suspended.contains(sink) {
+                i = next_i;
+                continue;
+            }
+            if let Err(Dropped) = pipe.rollback(self.commit_id.get()) {
+                self.feedback_pipes.remove(i);
             }
             i = next_i;
         }
         self.input_pipes
-            .retain_mut(|pipe| pipe.process(self.commit_id.get()).is_ok());
+            .retain_mut(|(_, pipe)| pipe.process(self.commit_id.get()).is_ok());
+    }
 
-        result
+    /// Runs `f` in a frame and commits. If `violation` fires, the frame is rolled
+    /// back and the violating tuples are returned. As with `rollback_to`, values sent
+    /// to frameless inputs inside `f` are not undone.
+    pub fn transaction<T: Clone, R>(
+        &mut self,
+        violation: &InterruptHandle<T>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> Result<R, TransactionError<T>> {
+        #[cfg(feature = "serde")]
+        let interrupted = self.interrupted;
+        let frame_id = self.push_frame();
+        let result = f(self);
+        assert_eq!(
+            self.frames.len(),
+            frame_id.depth + 1,
+            "unbalanced frames in transaction"
+        );
+        match self.commit() {
+            Ok(()) => {
+                self.frames.pop();
+                for (_, input) in self.input_pipes.iter_mut() {
+                    input.merge_frame();
+                }
+                Ok(result)
+            }
+            Err(err) => {
+                let err = match err {
+                    CommitError::Interrupted(interrupt_id) if interrupt_id == violation.id() => {
+                        TransactionError::Violation(violation.get().clone())
+                    }
+                    err => TransactionError::Commit(err),
+                };
+                self.rollback_to(frame_id);
+                #[cfg(feature = "serde")]
+                {
+                    self.interrupted = interrupted;
+                }
+                Err(err)
+            }
+        }
+    }
+
+    pub fn frame_depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn dataflow_graph(&self) -> DataflowGraph {
+        DataflowGraph::new(&self.sinks)
+    }
+
+    /// Captures the state of every input, all of which must be persisted. Operator and
+    /// output state is derived from the inputs, so the first commit after `restore`
+    /// rebuilds it rather than reading it from the snapshot.
+    #[cfg(feature = "serde")]
+    pub fn snapshot(&self) -> Result<Snapshot, SnapshotError> {
+        if !self.frames.is_empty() {
+            return Err(SnapshotError::InFrame);
+        }
+        if self.interrupted {
+            return Err(SnapshotError::Interrupted);
+        }
+        if !self.suspended.is_empty() {
+            return Err(SnapshotError::Suspended);
+        }
+        let graph = self.dataflow_graph();
+        let mut inputs = Vec::new();
+        for (input_id, pipe) in self.input_pipes.iter() {
+            let persisted = pipe.persisted().ok_or(SnapshotError::NotPersisted)?;
+            inputs.push((input_id.0, persisted.save()?));
+        }
+        Ok(Snapshot {
+            commit_id: self.commit_id.get().0,
+            nodes: snapshot::graph_nodes(&graph),
+            edges: graph.edges,
+            inputs,
+        })
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), SnapshotError> {
+        if self.commit_id.get() != CommitId(0) {
+            return Err(SnapshotError::AlreadyStarted);
+        }
+        if !snapshot.matches(&self.dataflow_graph()) {
+            return Err(SnapshotError::GraphMismatch);
+        }
+        let mut restored = HashSet::new();
+        for (input_id, state) in snapshot.inputs.iter() {
+            let input_id = InputId(*input_id);
+            let persisted = self
+                .input_pipes
+                .iter_mut()
+                .find(|(id, _)| *id == input_id)
+                .and_then(|(_, pipe)| pipe.persisted_mut())
+                .ok_or(SnapshotError::GraphMismatch)?;
+            persisted.load(state.clone())?;
+            restored.insert(input_id);
+        }
+        self.commit_id.set(CommitId(snapshot.commit_id));
+        self.one_pass();
+        let commit_id = self.commit_id.get();
+        let mut i = self.feedback_pipes.first_index();
+        while i.is_some() {
+            let next_i = self.feedback_pipes.next_index(i);
+            let (_, pipe) = self.feedback_pipes.get_mut(i).unwrap();
+            if let Err(Dropped) = pipe.restore(commit_id, &restored) {
+                self.feedback_pipes.remove(i);
+            }
+            i = next_i;
+        }
+        Ok(())
+    }
+
+    pub fn profile(&self) -> Profile {
+        Profile::new(&self.relational_graph)
+    }
+
+    pub fn set_profile_timing(&mut self, enabled: bool) {
+        for data in self.relational_graph.iter() {
+            data.stats.set_timed(enabled);
+        }
+    }
+
+    pub fn reset_profile(&mut self) {
+        for data in self.relational_graph.iter() {
+            data.stats.reset();
+        }
+    }
+
+    fn pipe_info(&self, sink: usize) -> PipeInfo {
+        let relation = representative(&self.sinks[sink].relation);
+        PipeInfo {
+            name: relation.name.clone(),
+            type_name: relation.type_name,
+        }
     }
 
     fn one_pass(&mut self) {
+        self.one_pass_changed();
+    }
+
+    fn one_pass_changed(&mut self) -> Vec<InputId> {
         self.commit_id.set(CommitId(self.commit_id.get().0 + 1));
-        self.input_pipes
-            .retain_mut(|pipe| pipe.process(self.commit_id.get()).is_ok());
+        let mut changed = Vec::new();
+        self.input_pipes.retain_mut(
+            |(input_id, pipe)| match pipe.process(self.commit_id.get()) {
+                Ok(ProcessResult::Changed) => {
+                    changed.push(*input_id);
+                    true
+                }
+                Ok(_) => true,
+                Err(Dropped) => false,
+            },
+        );
+        changed
     }
 }
 
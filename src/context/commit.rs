@@ -0,0 +1,152 @@
+use std::{any::Any, collections::HashMap, error::Error, fmt, hash::Hash, rc::Rc, time::Instant};
+
+use derivative::Derivative;
+
+use crate::value_count::ValueCount;
+
+use super::pipes::interrupt::InterruptId;
+
+#[derive(Clone, Debug, Default)]
+pub struct CommitLimits {
+    pub max_rounds: Option<usize>,
+    pub deadline: Option<Instant>,
+    pub detect_oscillation: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PipeInfo {
+    pub name: Option<String>,
+    pub type_name: &'static str,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommitError {
+    Interrupted(InterruptId),
+    ConstraintViolation {
+        name: String,
+        tuples: ViolatingTuples,
+    },
+    SinkFailed {
+        pipe: PipeInfo,
+        message: String,
+    },
+    RoundLimit {
+        rounds: usize,
+        pipe: PipeInfo,
+    },
+    Deadline {
+        rounds: usize,
+        pipe: PipeInfo,
+    },
+    Oscillation {
+        rounds: usize,
+        pipe: PipeInfo,
+    },
+}
+
+/// The tuples in a violated constraint, as a `Vec<T>` of the constraint's tuple type.
+/// Two values are equal only if they come from the same violation.
+#[derive(Clone)]
+pub struct ViolatingTuples {
+    len: usize,
+    tuples: Rc<dyn Any>,
+}
+
+#[derive(Clone, Debug, Derivative)]
+#[derivative(PartialEq(bound = "T: Eq + Hash"), Eq(bound = "T: Eq + Hash"))]
+pub enum TransactionError<T> {
+    Violation(HashMap<T, ValueCount>),
+    Commit(CommitError),
+}
+
+impl ViolatingTuples {
+    pub(crate) fn new<T: 'static>(tuples: Vec<T>) -> Self {
+        Self {
+            len: tuples.len(),
+            tuples: Rc::new(tuples),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&Vec<T>> {
+        self.tuples.downcast_ref()
+    }
+}
+
+impl fmt::Debug for ViolatingTuples {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ViolatingTuples")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ViolatingTuples {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.tuples, &other.tuples)
+    }
+}
+
+impl Eq for ViolatingTuples {}
+
+impl fmt::Display for PipeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} ({})", name, self.type_name),
+            None => write!(f, "{}", self.type_name),
+        }
+    }
+}
+
+impl fmt::Display for CommitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommitError::Interrupted(interrupt_id) => write!(f, "interrupted by {}", interrupt_id),
+            CommitError::ConstraintViolation { name, .. } => {
+                write!(f, "constraint {} violated", name)
+            }
+            CommitError::SinkFailed { pipe, message } => {
+                write!(f, "{} failed: {}", pipe, message)
+            }
+            CommitError::RoundLimit { rounds, pipe } => {
+                write!(f, "{} still changing after {} rounds", pipe, rounds)
+            }
+            CommitError::Deadline { rounds, pipe } => {
+                write!(
+                    f,
+                    "{} still changing at the deadline after {} rounds",
+                    pipe, rounds
+                )
+            }
+            CommitError::Oscillation { rounds, pipe } => {
+                write!(f, "{} repeated a delta after {} rounds", pipe, rounds)
+            }
+        }
+    }
+}
+
+impl Error for CommitError {}
+
+impl<T> fmt::Display for TransactionError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionError::Violation(tuples) => {
+                write!(
+                    f,
+                    "transaction violated a constraint with {} tuples",
+                    tuples.len()
+                )
+            }
+            TransactionError::Commit(err) => write!(f, "transaction failed: {}", err),
+        }
+    }
+}
+
+impl<T: fmt::Debug> Error for TransactionError<T> {}
@@ -1,26 +1,72 @@
+#[cfg(feature = "serde")]
+use std::collections::HashSet;
+
 use self::interrupt::InterruptId;
+use super::commit::ViolatingTuples;
 
+#[cfg(feature = "serde")]
+use super::InputId;
 use super::{CommitId, Dropped};
 
+pub(crate) mod bag;
+pub(crate) mod constraint;
 pub(crate) mod feedback;
 pub(crate) mod interrupt;
+#[cfg(feature = "serde")]
+pub(crate) mod persisted;
 #[cfg(feature = "redis")]
 pub(crate) mod redis;
+pub(crate) mod sink;
 pub(crate) mod tracked;
 pub(crate) mod untracked;
 pub(crate) mod values;
 
 pub(crate) trait Processable {
     fn process(&mut self, commit_id: CommitId) -> Result<ProcessResult, Dropped>;
+    fn start_commit(&mut self) {}
+    fn rollback(&mut self, commit_id: CommitId) -> Result<(), Dropped> {
+        self.process(commit_id).map(|_| ())
+    }
+    fn delta_hash(&self) -> Option<u64> {
+        None
+    }
+    fn finish(&mut self) -> ProcessResult {
+        ProcessResult::Unchanged
+    }
+    fn close(&mut self) -> ProcessResult {
+        ProcessResult::Unchanged
+    }
+    #[cfg(feature = "serde")]
+    fn restore(
+        &mut self,
+        commit_id: CommitId,
+        _restored: &HashSet<InputId>,
+    ) -> Result<(), Dropped> {
+        self.process(commit_id).map(|_| ())
+    }
 }
 
 pub(crate) trait PipeT: Processable {
     fn push_frame(&mut self);
     fn pop_frame(&mut self, commit_id: CommitId) -> Result<(), Dropped>;
+    fn merge_frame(&mut self);
+    fn is_frameless(&self) -> bool {
+        false
+    }
+    #[cfg(feature = "serde")]
+    fn persisted(&self) -> Option<&dyn persisted::Persist> {
+        None
+    }
+    #[cfg(feature = "serde")]
+    fn persisted_mut(&mut self) -> Option<&mut dyn persisted::Persist> {
+        None
+    }
 }
 
 pub(crate) enum ProcessResult {
     Changed,
     Unchanged,
     Interrupted(InterruptId),
+    Violated(String, ViolatingTuples),
+    Failed(String),
 }
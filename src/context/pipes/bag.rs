@@ -0,0 +1,137 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use derivative::Derivative;
+
+use crate::{
+    channel,
+    context::{CommitId, Dropped},
+    generic_map::AddMap,
+    nullable::Nullable,
+    value_count::ValueCount,
+    who::Who,
+};
+
+#[cfg(feature = "serde")]
+use super::persisted::Persist;
+use super::{PipeT, ProcessResult, Processable};
+
+pub(crate) struct BagInputPipe<T> {
+    receiver: channel::Receiver<(T, Who)>,
+    sender: channel::Sender<(T, ValueCount)>,
+    values: HashMap<T, ValueCount>,
+    emitted: HashMap<T, ValueCount>,
+    frame_changes: Vec<Frame<T>>,
+    changed_keys_scratch: HashSet<T>,
+}
+
+impl<T> BagInputPipe<T> {
+    pub(crate) fn new(
+        receiver: channel::Receiver<(T, Who)>,
+        sender: channel::Sender<(T, ValueCount)>,
+    ) -> Self {
+        BagInputPipe {
+            receiver,
+            sender,
+            values: HashMap::new(),
+            emitted: HashMap::new(),
+            frame_changes: Vec::new(),
+            changed_keys_scratch: HashSet::new(),
+        }
+    }
+}
+
+impl<T: Eq + Hash + Clone> Processable for BagInputPipe<T> {
+    fn process(&mut self, _commit_id: CommitId) -> Result<ProcessResult, Dropped> {
+        let mut result = ProcessResult::Unchanged;
+        while let Some((value, who)) = self.receiver.try_recv() {
+            self.changed_keys_scratch.insert(value.clone());
+            if let Who::User(count) = who {
+                if let Some(frame) = self.frame_changes.last_mut() {
+                    frame.user_values.add((value.clone(), count));
+                }
+            }
+            self.values.add((value, who.value_count()));
+        }
+        for value in self.changed_keys_scratch.drain() {
+            let mut delta = self.values.get(&value).copied().unwrap_or_default();
+            delta -= self.emitted.get(&value).copied().unwrap_or_default();
+            if delta.is_empty() {
+                continue;
+            }
+            result = ProcessResult::Changed;
+            if let Some(frame) = self.frame_changes.last_mut() {
+                frame.emitted.add((value.clone(), delta));
+            }
+            self.emitted.add((value.clone(), delta));
+            if self.sender.send((value, delta)).is_err() {
+                return Err(Dropped);
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> Persist for BagInputPipe<T>
+where
+    T: Eq + Hash + Clone + serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn save(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(
+            self.values
+                .iter()
+                .map(|(value, &count)| (value, count))
+                .collect::<Vec<_>>(),
+        )
+    }
+    fn load(&mut self, state: serde_json::Value) -> serde_json::Result<()> {
+        let values: Vec<(T, ValueCount)> = serde_json::from_value(state)?;
+        self.changed_keys_scratch
+            .extend(values.iter().map(|(value, _)| value.clone()));
+        self.values = values.into_iter().collect();
+        Ok(())
+    }
+}
+
+impl<T: Eq + Hash + Clone> PipeT for BagInputPipe<T> {
+    fn push_frame(&mut self) {
+        self.frame_changes.push(Frame::default());
+    }
+    fn pop_frame(&mut self, _commit_id: CommitId) -> Result<(), Dropped> {
+        let frame = self.frame_changes.pop().unwrap();
+        for (value, count) in frame.user_values {
+            self.values.add((value, -count));
+        }
+        for (value, count) in frame.emitted {
+            // Feedback received during the frame is still counted in `values` until the
+            // feedback pipes retract it, so recheck these keys on the next pass.
+            self.changed_keys_scratch.insert(value.clone());
+            self.emitted.add((value.clone(), -count));
+            if self.sender.send((value, -count)).is_err() {
+                return Err(Dropped);
+            }
+        }
+        Ok(())
+    }
+    fn merge_frame(&mut self) {
+        let frame = self.frame_changes.pop().unwrap();
+        if let Some(parent) = self.frame_changes.last_mut() {
+            for entry in frame.user_values {
+                parent.user_values.add(entry);
+            }
+            for entry in frame.emitted {
+                parent.emitted.add(entry);
+            }
+        }
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+struct Frame<T> {
+    user_values: HashMap<T, ValueCount>,
+    emitted: HashMap<T, ValueCount>,
+}
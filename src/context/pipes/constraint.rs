@@ -0,0 +1,72 @@
+use std::{
+    cell::{Ref, RefCell},
+    collections::HashMap,
+    hash::Hash,
+    rc::Rc,
+};
+
+use crate::{
+    context::{CommitId, Dropped, ViolatingTuples},
+    op::Op,
+    relation::RelationInner,
+    value_count::ValueCount,
+};
+
+use super::{ProcessResult, Processable};
+
+pub(crate) struct Constraint<T, C> {
+    name: String,
+    relation: RelationInner<T, C>,
+    values: Rc<RefCell<HashMap<T, ValueCount>>>,
+}
+
+impl<T, C> Constraint<T, C> {
+    pub(crate) fn new(name: String, relation: RelationInner<T, C>) -> (Self, ConstraintHandle<T>) {
+        let values = Rc::new(RefCell::new(HashMap::new()));
+        let handle = ConstraintHandle {
+            name: name.clone(),
+            values: values.clone(),
+        };
+        let constraint = Self {
+            name,
+            relation,
+            values,
+        };
+        (constraint, handle)
+    }
+}
+
+impl<T: Eq + Hash + Clone + 'static, C: Op<T>> Processable for Constraint<T, C> {
+    fn process(&mut self, commit_id: CommitId) -> Result<ProcessResult, Dropped> {
+        let mut values = self.values.borrow_mut();
+        self.relation.dump_to_map(commit_id, &mut values);
+        if values.is_empty() {
+            Ok(ProcessResult::Unchanged)
+        } else {
+            let tuples = values
+                .iter()
+                .filter(|(_, count)| count.0 > 0)
+                .map(|(x, _)| x.clone())
+                .collect();
+            Ok(ProcessResult::Violated(
+                self.name.clone(),
+                ViolatingTuples::new(tuples),
+            ))
+        }
+    }
+}
+
+pub struct ConstraintHandle<T> {
+    name: String,
+    values: Rc<RefCell<HashMap<T, ValueCount>>>,
+}
+
+impl<T> ConstraintHandle<T> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get(&self) -> Ref<'_, HashMap<T, ValueCount>> {
+        self.values.borrow()
+    }
+}
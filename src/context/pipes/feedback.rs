@@ -1,3 +1,12 @@
+#[cfg(feature = "serde")]
+use std::collections::HashSet;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+#[cfg(feature = "serde")]
+use crate::context::InputId;
 use crate::{
     context::{CommitId, Dropped},
     op::Op,
@@ -11,28 +20,52 @@ use super::{ProcessResult, Processable};
 pub(crate) struct FeedbackPipe<T, C> {
     relation: RelationInner<T, C>,
     input: Input<T>,
+    delta_hash: u64,
 }
 
 impl<T, C> FeedbackPipe<T, C> {
     pub(crate) fn new(relation: RelationInner<T, C>, input: Input<T>) -> Self {
-        FeedbackPipe { relation, input }
+        FeedbackPipe {
+            relation,
+            input,
+            delta_hash: 0,
+        }
     }
 }
 
-impl<T, C: Op<T>> Processable for FeedbackPipe<T, C> {
+impl<T: Hash, C: Op<T>> Processable for FeedbackPipe<T, C> {
     fn process(&mut self, commit_id: CommitId) -> Result<ProcessResult, Dropped> {
         let mut any_dropped = false;
         let mut result = ProcessResult::Unchanged;
+        // Summing per-value hashes scaled by count makes the hash independent of the order
+        // and grouping in which the delta arrives.
+        let mut delta_hash = 0u64;
         self.relation.foreach(commit_id, |value, count| {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            delta_hash = delta_hash.wrapping_add(hasher.finish().wrapping_mul(count.0 as u64));
             if self.input.send_count(value, Who::Feedback(count)).is_err() {
                 any_dropped = true;
             }
             result = ProcessResult::Changed;
         });
+        self.delta_hash = delta_hash;
         if any_dropped {
             Err(Dropped)
         } else {
             Ok(result)
         }
     }
+    fn delta_hash(&self) -> Option<u64> {
+        (self.delta_hash != 0).then_some(self.delta_hash)
+    }
+    #[cfg(feature = "serde")]
+    fn restore(&mut self, commit_id: CommitId, restored: &HashSet<InputId>) -> Result<(), Dropped> {
+        if !restored.contains(&self.input.input_id) {
+            return self.process(commit_id).map(|_| ());
+        }
+        // The restored input already counts this relation's contents as feedback.
+        self.relation.foreach(commit_id, |_, _| ());
+        Ok(())
+    }
 }
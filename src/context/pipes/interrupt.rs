@@ -1,7 +1,15 @@
-use std::{collections::HashMap, hash::Hash};
+use std::{
+    cell::{Ref, RefCell},
+    collections::HashMap,
+    hash::Hash,
+    rc::Rc,
+};
+
+use derivative::Derivative;
 
 use crate::{
     context::{CommitId, Dropped},
+    generic_map::AddMap,
     op::Op,
     relation::RelationInner,
     value_count::ValueCount,
@@ -11,29 +19,84 @@ use super::{ProcessResult, Processable};
 
 pub type InterruptId = usize;
 
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+struct InterruptState<T> {
+    values: HashMap<T, ValueCount>,
+    delta: HashMap<T, ValueCount>,
+}
+
 pub struct Interrupt<T, C> {
     relation: RelationInner<T, C>,
     interrupt_id: InterruptId,
-    values: HashMap<T, ValueCount>,
+    state: Rc<RefCell<InterruptState<T>>>,
+    changes: HashMap<T, ValueCount>,
 }
 
 impl<T, C> Interrupt<T, C> {
-    pub(crate) fn new(interrupt_id: InterruptId, relation: RelationInner<T, C>) -> Self {
-        Self {
+    pub(crate) fn new(
+        interrupt_id: InterruptId,
+        relation: RelationInner<T, C>,
+    ) -> (Self, InterruptHandle<T>) {
+        let state = Rc::new(RefCell::new(InterruptState::default()));
+        let handle = InterruptHandle {
+            interrupt_id,
+            state: state.clone(),
+        };
+        let interrupt = Self {
             relation,
             interrupt_id,
-            values: HashMap::new(),
-        }
+            state,
+            changes: HashMap::new(),
+        };
+        (interrupt, handle)
     }
 }
 
-impl<T: Eq + Hash, C: Op<T>> Processable for Interrupt<T, C> {
+impl<T: Eq + Hash + Clone, C: Op<T>> Processable for Interrupt<T, C> {
     fn process(&mut self, commit_id: CommitId) -> Result<ProcessResult, Dropped> {
-        self.relation.dump_to_map(commit_id, &mut self.values);
-        if self.values.is_empty() {
+        self.relation.dump_to_map(commit_id, &mut self.changes);
+        let mut state = self.state.borrow_mut();
+        let InterruptState { values, delta } = &mut *state;
+        for (value, count) in self.changes.drain() {
+            delta.add((value.clone(), count));
+            values.add((value, count));
+        }
+        if values.is_empty() {
             Ok(ProcessResult::Unchanged)
         } else {
             Ok(ProcessResult::Interrupted(self.interrupt_id))
         }
     }
+    fn start_commit(&mut self) {
+        self.state.borrow_mut().delta.clear();
+    }
+    // A rollback changes the contents but leaves the delta of the last commit.
+    fn rollback(&mut self, commit_id: CommitId) -> Result<(), Dropped> {
+        self.relation.dump_to_map(commit_id, &mut self.changes);
+        let mut state = self.state.borrow_mut();
+        for (value, count) in self.changes.drain() {
+            state.values.add((value, count));
+        }
+        Ok(())
+    }
+}
+
+pub struct InterruptHandle<T> {
+    interrupt_id: InterruptId,
+    state: Rc<RefCell<InterruptState<T>>>,
+}
+
+impl<T> InterruptHandle<T> {
+    pub fn id(&self) -> InterruptId {
+        self.interrupt_id
+    }
+
+    pub fn get(&self) -> Ref<'_, HashMap<T, ValueCount>> {
+        Ref::map(self.state.borrow(), |state| &state.values)
+    }
+
+    pub fn delta(&self) -> Ref<'_, HashMap<T, ValueCount>> {
+        Ref::map(self.state.borrow(), |state| &state.delta)
+    }
 }
@@ -0,0 +1,37 @@
+use crate::context::{CommitId, Dropped};
+
+use super::{PipeT, ProcessResult, Processable};
+
+pub(crate) trait Persist {
+    fn save(&self) -> serde_json::Result<serde_json::Value>;
+    fn load(&mut self, state: serde_json::Value) -> serde_json::Result<()>;
+}
+
+pub(crate) struct Persisted<P>(pub(crate) P);
+
+impl<P: Processable> Processable for Persisted<P> {
+    fn process(&mut self, commit_id: CommitId) -> Result<ProcessResult, Dropped> {
+        self.0.process(commit_id)
+    }
+}
+
+impl<P: PipeT + Persist> PipeT for Persisted<P> {
+    fn push_frame(&mut self) {
+        self.0.push_frame()
+    }
+    fn pop_frame(&mut self, commit_id: CommitId) -> Result<(), Dropped> {
+        self.0.pop_frame(commit_id)
+    }
+    fn merge_frame(&mut self) {
+        self.0.merge_frame()
+    }
+    fn is_frameless(&self) -> bool {
+        self.0.is_frameless()
+    }
+    fn persisted(&self) -> Option<&dyn Persist> {
+        Some(&self.0)
+    }
+    fn persisted_mut(&mut self) -> Option<&mut dyn Persist> {
+        Some(&mut self.0)
+    }
+}
@@ -1,8 +1,9 @@
 use std::collections::{HashMap, HashSet};
+use std::error::Error;
 use std::fmt::Debug;
 use std::hash::Hash;
-
-use redis::{Commands, ToRedisArgs};
+use std::thread;
+use std::time::Duration;
 
 use crate::generic_map::AddMap;
 use crate::{
@@ -14,65 +15,446 @@ use crate::{
 
 use super::{ProcessResult, Processable};
 
-impl<T: Debug, C> Drop for RedisPipe<T, C> {
+#[derive(Clone, Copy, Debug)]
+pub struct RedisRetry {
+    pub retries: usize,
+    pub delay: Duration,
+}
+
+impl Default for RedisRetry {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            delay: Duration::from_millis(100),
+        }
+    }
+}
+
+type Encoded = Result<Vec<u8>, Box<dyn Error>>;
+type EncodeKey<'a, T> = Box<dyn Fn(&T) -> Encoded + 'a>;
+type EncodeValue<'a, T> = Box<dyn Fn(&T, ValueCount) -> Encoded + 'a>;
+
+pub struct RedisEncoding<'a, T> {
+    key: EncodeKey<'a, T>,
+    value: EncodeValue<'a, T>,
+}
+
+impl<'a, T> RedisEncoding<'a, T> {
+    pub fn new(
+        key: impl Fn(&T) -> Encoded + 'a,
+        value: impl Fn(&T, ValueCount) -> Encoded + 'a,
+    ) -> Self {
+        Self {
+            key: Box::new(key),
+            value: Box::new(value),
+        }
+    }
+}
+
+impl<'a, T: Debug + 'a> RedisEncoding<'a, T> {
+    pub fn debug() -> Self {
+        Self::new(|t| Ok(format!("{:?}", t).into_bytes()), encode_count)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a, T: serde::Serialize + 'a> RedisEncoding<'a, T> {
+    pub fn json() -> Self {
+        Self::new(|t| Ok(serde_json::to_vec(t)?), encode_count)
+    }
+}
+
+impl<'a, K, V> RedisEncoding<'a, (K, V)> {
+    pub fn split(key: impl Fn(&K) -> Encoded + 'a, value: impl Fn(&V) -> Encoded + 'a) -> Self {
+        Self::new(move |(k, _)| key(k), move |(_, v), _| value(v))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a, K: serde::Serialize + 'a, V: serde::Serialize + 'a> RedisEncoding<'a, (K, V)> {
+    pub fn split_json() -> Self {
+        Self::split(
+            |k| Ok(serde_json::to_vec(k)?),
+            |v| Ok(serde_json::to_vec(v)?),
+        )
+    }
+}
+
+fn encode_count<T>(_: &T, count: ValueCount) -> Encoded {
+    Ok(count.0.to_string().into_bytes())
+}
+
+pub enum RedisLayout<'a, T> {
+    Keys,
+    Hash,
+    Set,
+    SortedSet(Box<dyn Fn(&T) -> f64 + 'a>),
+}
+
+impl<'a, T> RedisLayout<'a, T> {
+    pub fn sorted_set(score: impl Fn(&T) -> f64 + 'a) -> Self {
+        RedisLayout::SortedSet(Box::new(score))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Write {
+    Set(Vec<u8>, Vec<u8>),
+    Del(Vec<u8>),
+    HSet(Vec<u8>, Vec<u8>),
+    HDel(Vec<u8>),
+    SAdd(Vec<u8>),
+    SRem(Vec<u8>),
+    ZAdd(Vec<u8>, f64),
+    ZRem(Vec<u8>),
+}
+
+// What the Redis side should hold, kept apart from the connection so the writes for a
+// commit can be planned (and tested) without a server.
+struct RedisState<'a, T> {
+    encoding: RedisEncoding<'a, T>,
+    layout: RedisLayout<'a, T>,
+    values: HashMap<T, ValueCount>,
+    // The present tuples behind each encoded key, so a key is only deleted once no
+    // tuple maps to it.
+    tuples: HashMap<Vec<u8>, HashSet<T>>,
+    changed: HashSet<T>,
+}
+
+impl<'a, T: Clone + Eq + Hash> RedisState<'a, T> {
+    fn new(encoding: RedisEncoding<'a, T>, layout: RedisLayout<'a, T>) -> Self {
+        Self {
+            encoding,
+            layout,
+            values: HashMap::new(),
+            tuples: HashMap::new(),
+            changed: HashSet::new(),
+        }
+    }
+
+    fn receive(&mut self, t: T, count: ValueCount) {
+        self.changed.insert(t.clone());
+        self.values.add((t, count));
+    }
+
+    // Updating `tuples` only depends on which tuples are present, so planning again
+    // after a failed write is harmless.
+    fn plan(&mut self) -> Result<Vec<Write>, String> {
+        let mut keys = Vec::new();
+        for t in self.changed.iter() {
+            let key = (self.encoding.key)(t).map_err(|err| err.to_string())?;
+            if self.values.contains_key(t) {
+                self.tuples
+                    .entry(key.clone())
+                    .or_default()
+                    .insert(t.clone());
+            } else if let Some(tuples) = self.tuples.get_mut(&key) {
+                tuples.remove(t);
+                if tuples.is_empty() {
+                    self.tuples.remove(&key);
+                }
+            }
+            keys.push(key);
+        }
+        keys.sort();
+        keys.dedup();
+        let mut writes = Vec::new();
+        for key in keys {
+            let write = match self.tuples.get(&key) {
+                None => match self.layout {
+                    RedisLayout::Keys => Write::Del(key),
+                    RedisLayout::Hash => Write::HDel(key),
+                    RedisLayout::Set => Write::SRem(key),
+                    RedisLayout::SortedSet(_) => Write::ZRem(key),
+                },
+                // A set member stands for any number of tuples, but a key, hash field or
+                // scored member can only hold one value.
+                Some(_) if matches!(self.layout, RedisLayout::Set) => Write::SAdd(key),
+                Some(tuples) => {
+                    let mut tuples = tuples.iter();
+                    let t = tuples.next().unwrap();
+                    let value = |t: &T| match &self.layout {
+                        RedisLayout::SortedSet(score) => Ok(score(t).to_string().into_bytes()),
+                        _ => {
+                            (self.encoding.value)(t, self.values[t]).map_err(|err| err.to_string())
+                        }
+                    };
+                    let first = value(t)?;
+                    for other in tuples {
+                        if value(other)? != first {
+                            return Err(format!(
+                                "tuples with different values map to the Redis key {}",
+                                String::from_utf8_lossy(&key)
+                            ));
+                        }
+                    }
+                    match &self.layout {
+                        RedisLayout::Keys => Write::Set(key, first),
+                        RedisLayout::Hash => Write::HSet(key, first),
+                        RedisLayout::Set => unreachable!(),
+                        RedisLayout::SortedSet(score) => Write::ZAdd(key, score(t)),
+                    }
+                }
+            };
+            writes.push(write);
+        }
+        Ok(writes)
+    }
+}
+
+fn prefixed(name: &str, key: &[u8]) -> Vec<u8> {
+    let mut prefixed = format!("{}:", name).into_bytes();
+    prefixed.extend(key);
+    prefixed
+}
+
+fn pipeline(name: &str, writes: Vec<Write>) -> redis::Pipeline {
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    for write in writes {
+        match write {
+            Write::Set(key, value) => pipe.set(prefixed(name, &key), value),
+            Write::Del(key) => pipe.del(prefixed(name, &key)),
+            Write::HSet(field, value) => pipe.hset(name, field, value),
+            Write::HDel(field) => pipe.hdel(name, field),
+            Write::SAdd(member) => pipe.sadd(name, member),
+            Write::SRem(member) => pipe.srem(name, member),
+            Write::ZAdd(member, score) => pipe.zadd(name, member, score),
+            Write::ZRem(member) => pipe.zrem(name, member),
+        }
+        .ignore();
+    }
+    pipe
+}
+
+// Dropping only makes one attempt, since it cannot report a failure and should not
+// block on retries. `ExecutionContext::close` retries and returns the error.
+impl<T, C> Drop for RedisPipe<'_, T, C> {
     fn drop(&mut self) {
-        for (value, _) in self.values.iter() {
-            let mut connection = self.client.get_connection().unwrap();
-            connection
-                .del(RedisKey(&self.name, value))
-                .unwrap_or_else(|err| log::error!("Redis error: {}", err));
+        if self.state.tuples.is_empty() {
+            return;
         }
+        let pipe = self.cleanup();
+        self.query_once(&pipe)
+            .unwrap_or_else(|err| log::error!("Redis error: {}", err));
     }
 }
 
-pub struct RedisPipe<T: Debug, C> {
+pub struct RedisPipe<'a, T, C> {
     name: String,
     relation: RelationInner<T, C>,
-    values: HashMap<T, ValueCount>,
+    state: RedisState<'a, T>,
     client: redis::Client,
+    connection: Option<redis::Connection>,
+    retry: RedisRetry,
     changed_values_scratch: HashMap<T, ValueCount>,
-    changed_keys_scratch: HashSet<T>,
 }
-impl<T: Debug, C> RedisPipe<T, C> {
-    pub(crate) fn new(name: String, relation: RelationInner<T, C>, client: redis::Client) -> Self {
+
+impl<'a, T: Clone + Eq + Hash, C> RedisPipe<'a, T, C> {
+    pub(crate) fn new(
+        name: String,
+        relation: RelationInner<T, C>,
+        encoding: RedisEncoding<'a, T>,
+        layout: RedisLayout<'a, T>,
+        client: redis::Client,
+        retry: RedisRetry,
+    ) -> Self {
         Self {
             name,
             relation,
-            values: HashMap::new(),
+            state: RedisState::new(encoding, layout),
             client,
+            connection: None,
+            retry,
             changed_values_scratch: HashMap::new(),
-            changed_keys_scratch: HashSet::new(),
         }
     }
 }
 
-impl<T: Clone + Eq + Hash + Debug, C: Op<T>> Processable for RedisPipe<T, C> {
+impl<T, C> RedisPipe<'_, T, C> {
+    fn query_once(&mut self, pipe: &redis::Pipeline) -> redis::RedisResult<()> {
+        let result = match &mut self.connection {
+            Some(connection) => pipe.query(connection),
+            None => self.client.get_connection().and_then(|mut connection| {
+                let result = pipe.query(&mut connection);
+                self.connection = Some(connection);
+                result
+            }),
+        };
+        if result.is_err() {
+            self.connection = None;
+        }
+        result
+    }
+
+    fn query(&mut self, pipe: &redis::Pipeline) -> redis::RedisResult<()> {
+        let mut attempt = 0;
+        loop {
+            match self.query_once(pipe) {
+                Ok(()) => return Ok(()),
+                Err(err) if attempt < self.retry.retries => {
+                    log::warn!("Redis error, retrying: {}", err);
+                    attempt += 1;
+                    thread::sleep(self.retry.delay);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    // Removes everything this pipe has written.
+    fn cleanup(&self) -> redis::Pipeline {
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        match self.state.layout {
+            RedisLayout::Keys => {
+                for key in self.state.tuples.keys() {
+                    pipe.del(prefixed(&self.name, key)).ignore();
+                }
+            }
+            _ => {
+                pipe.del(&self.name).ignore();
+            }
+        }
+        pipe
+    }
+}
+
+impl<T: Clone + Eq + Hash, C: Op<T>> Processable for RedisPipe<'_, T, C> {
     fn process(&mut self, commit_id: CommitId) -> Result<ProcessResult, Dropped> {
         self.relation
             .dump_to_map(commit_id, &mut self.changed_values_scratch);
-        for (k, v) in self.changed_values_scratch.drain() {
-            self.changed_keys_scratch.insert(k.clone());
-            self.values.add((k, v));
-        }
-        let mut connection = self.client.get_connection().unwrap();
-        for k in self.changed_keys_scratch.drain() {
-            match self.values.get(&k) {
-                Some(&v) => connection.set(RedisKey(&self.name, &k), v).unwrap(),
-                None => connection.del(RedisKey(&self.name, &k)).unwrap(),
-            }
+        for (t, count) in self.changed_values_scratch.drain() {
+            self.state.receive(t, count);
         }
         Ok(ProcessResult::Unchanged)
     }
+    fn finish(&mut self) -> ProcessResult {
+        if self.state.changed.is_empty() {
+            return ProcessResult::Unchanged;
+        }
+        let writes = match self.state.plan() {
+            Ok(writes) => writes,
+            Err(message) => return ProcessResult::Failed(message),
+        };
+        match self.query(&pipeline(&self.name, writes)) {
+            Ok(()) => {
+                self.state.changed.clear();
+                ProcessResult::Unchanged
+            }
+            // The changed tuples are kept and written again with the next commit.
+            Err(err) => ProcessResult::Failed(err.to_string()),
+        }
+    }
+    fn close(&mut self) -> ProcessResult {
+        if self.state.tuples.is_empty() {
+            return ProcessResult::Unchanged;
+        }
+        let pipe = self.cleanup();
+        match self.query(&pipe) {
+            Ok(()) => {
+                self.state.tuples.clear();
+                ProcessResult::Unchanged
+            }
+            Err(err) => ProcessResult::Failed(err.to_string()),
+        }
+    }
 }
 
-struct RedisKey<'a, T>(&'a str, &'a T);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(layout: RedisLayout<'static, (char, u32)>) -> RedisState<'static, (char, u32)> {
+        let encoding = RedisEncoding::split(
+            |k: &char| Ok(k.to_string().into_bytes()),
+            |v: &u32| Ok(v.to_string().into_bytes()),
+        );
+        RedisState::new(encoding, layout)
+    }
+
+    fn commit(
+        state: &mut RedisState<(char, u32)>,
+        changes: &[((char, u32), isize)],
+    ) -> Result<Vec<Write>, String> {
+        for &(t, count) in changes {
+            state.receive(t, ValueCount(count));
+        }
+        let writes = state.plan()?;
+        state.changed.clear();
+        Ok(writes)
+    }
+
+    fn bytes(s: &str) -> Vec<u8> {
+        s.as_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_shared_key_is_kept_until_last_tuple_goes() {
+        let mut state = state(RedisLayout::Hash);
+        assert_eq!(
+            commit(&mut state, &[(('a', 1), 1), (('b', 2), 1)]),
+            Ok(vec![
+                Write::HSet(bytes("a"), bytes("1")),
+                Write::HSet(bytes("b"), bytes("2")),
+            ])
+        );
+        assert_eq!(
+            commit(&mut state, &[(('a', 1), -1), (('a', 3), 1)]),
+            Ok(vec![Write::HSet(bytes("a"), bytes("3"))])
+        );
+        assert!(commit(&mut state, &[(('a', 4), 1)]).is_err());
+        assert_eq!(
+            commit(&mut state, &[(('a', 3), -1)]),
+            Ok(vec![Write::HSet(bytes("a"), bytes("4"))])
+        );
+        assert_eq!(
+            commit(&mut state, &[(('a', 4), -1)]),
+            Ok(vec![Write::HDel(bytes("a"))])
+        );
+    }
+
+    #[test]
+    fn test_set_member_stands_for_all_its_tuples() {
+        let mut state = state(RedisLayout::Set);
+        assert_eq!(
+            commit(&mut state, &[(('a', 1), 1), (('a', 2), 1)]),
+            Ok(vec![Write::SAdd(bytes("a"))])
+        );
+        assert_eq!(
+            commit(&mut state, &[(('a', 1), -1)]),
+            Ok(vec![Write::SAdd(bytes("a"))])
+        );
+        assert_eq!(
+            commit(&mut state, &[(('a', 2), -1)]),
+            Ok(vec![Write::SRem(bytes("a"))])
+        );
+    }
+
+    #[test]
+    fn test_sorted_set_and_keys_layouts() {
+        let mut state = self::state(RedisLayout::sorted_set(|&(_, v)| v as f64));
+        assert_eq!(
+            commit(&mut state, &[(('a', 2), 1), (('b', 1), 1)]),
+            Ok(vec![
+                Write::ZAdd(bytes("a"), 2.0),
+                Write::ZAdd(bytes("b"), 1.0),
+            ])
+        );
+        assert!(commit(&mut state, &[(('b', 5), 1)]).is_err());
+        assert_eq!(
+            commit(&mut state, &[(('b', 1), -1), (('b', 5), -1)]),
+            Ok(vec![Write::ZRem(bytes("b"))])
+        );
 
-impl<T: Debug> ToRedisArgs for RedisKey<'_, T> {
-    fn write_redis_args<W>(&self, out: &mut W)
-    where
-        W: ?Sized + redis::RedisWrite,
-    {
-        let RedisKey(name, key) = self;
-        out.write_arg(format!("{}:{:?}", name, key).as_bytes());
+        let mut state = self::state(RedisLayout::Keys);
+        assert_eq!(
+            commit(&mut state, &[(('a', 1), 1)]),
+            Ok(vec![Write::Set(bytes("a"), bytes("1"))])
+        );
+        assert_eq!(
+            commit(&mut state, &[(('a', 1), -1)]),
+            Ok(vec![Write::Del(bytes("a"))])
+        );
     }
 }
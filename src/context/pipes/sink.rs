@@ -0,0 +1,56 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{
+    context::{CommitId, Dropped},
+    op::Op,
+    relation::RelationInner,
+    sink::{Sink, SinkError},
+    value_count::ValueCount,
+};
+
+use super::{ProcessResult, Processable};
+
+pub(crate) struct SinkPipe<T, C, S> {
+    relation: RelationInner<T, C>,
+    sink: S,
+    changes: HashMap<T, ValueCount>,
+}
+
+impl<T, C, S> SinkPipe<T, C, S> {
+    pub(crate) fn new(relation: RelationInner<T, C>, sink: S) -> Self {
+        Self {
+            relation,
+            sink,
+            changes: HashMap::new(),
+        }
+    }
+}
+
+impl<T, C, S> Processable for SinkPipe<T, C, S>
+where
+    T: Eq + Hash,
+    C: Op<T>,
+    S: Sink<T>,
+{
+    fn process(&mut self, commit_id: CommitId) -> Result<ProcessResult, Dropped> {
+        self.relation.dump_to_map(commit_id, &mut self.changes);
+        Ok(ProcessResult::Unchanged)
+    }
+    fn finish(&mut self) -> ProcessResult {
+        if self.changes.is_empty() {
+            return ProcessResult::Unchanged;
+        }
+        match self.sink.receive(&self.changes) {
+            Ok(()) => {
+                self.changes.clear();
+                ProcessResult::Unchanged
+            }
+            Err(SinkError::Interrupt(interrupt_id)) => {
+                self.changes.clear();
+                ProcessResult::Interrupted(interrupt_id)
+            }
+            // Keep the changes so they are delivered again, merged with later ones.
+            Err(SinkError::Failed(message)) => ProcessResult::Failed(message),
+        }
+    }
+}
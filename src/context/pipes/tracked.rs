@@ -9,10 +9,13 @@ use crate::{
     channel,
     context::{CommitId, Dropped},
     generic_map::AddMap,
+    nullable::Nullable,
     value_count::ValueCount,
     who::Who,
 };
 
+#[cfg(feature = "serde")]
+use super::persisted::Persist;
 use super::{values::Values, PipeT, ProcessResult, Processable};
 
 pub(crate) struct TrackedInputPipe<T> {
@@ -21,6 +24,7 @@ pub(crate) struct TrackedInputPipe<T> {
     received: Values<T>,
     frame_changes: Vec<Frame<T>>,
     changed_keys_scratch: HashSet<T>,
+    removed_keys_scratch: HashSet<T>,
 }
 
 impl<T> TrackedInputPipe<T> {
@@ -34,6 +38,7 @@ impl<T> TrackedInputPipe<T> {
             received: Values::default(),
             frame_changes: Vec::new(),
             changed_keys_scratch: HashSet::new(),
+            removed_keys_scratch: HashSet::new(),
         }
     }
 }
@@ -43,30 +48,60 @@ impl<T: Eq + Hash + Clone> Processable for TrackedInputPipe<T> {
         let mut result = ProcessResult::Unchanged;
         while let Some((value, who)) = self.receiver.try_recv() {
             self.changed_keys_scratch.insert(value.clone());
-            if matches!(who, Who::User) {
+            let user = matches!(who, Who::User(_));
+            let count = self.received.receive(value.clone(), who);
+            if user && !count.is_empty() {
                 if let Some(frame) = self.frame_changes.last_mut() {
-                    frame.user_values.add((value.clone(), ValueCount(1)));
+                    frame.user_values.add((value.clone(), count));
+                }
+                if count.0 < 0 {
+                    self.removed_keys_scratch.insert(value);
                 }
             }
-            self.received.values.add((value, who.value_count()));
         }
         for value in self.changed_keys_scratch.drain() {
-            if self.received.values.contains_key(&value) {
+            if self.received.is_present(&value) {
                 if self.received.seen.insert(value.clone()) {
                     result = ProcessResult::Changed;
                     if let Some(frame) = self.frame_changes.last_mut() {
-                        frame.seen.insert(value.clone());
+                        frame.see(value.clone());
                     }
                     if self.sender.send((value, ValueCount(1))).is_err() {
                         return Err(Dropped);
                     }
                 }
+            } else if self.removed_keys_scratch.contains(&value)
+                && self.received.seen.remove(&value)
+            {
+                result = ProcessResult::Changed;
+                if let Some(frame) = self.frame_changes.last_mut() {
+                    frame.unsee(value.clone());
+                }
+                if self.sender.send((value, ValueCount(-1))).is_err() {
+                    return Err(Dropped);
+                }
             }
         }
+        self.removed_keys_scratch.clear();
         Ok(result)
     }
 }
 
+#[cfg(feature = "serde")]
+impl<T> Persist for TrackedInputPipe<T>
+where
+    T: Eq + Hash + Clone + serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn save(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self.received.save())
+    }
+    fn load(&mut self, state: serde_json::Value) -> serde_json::Result<()> {
+        let state = serde_json::from_value(state)?;
+        self.received.load(state, &mut self.sender);
+        Ok(())
+    }
+}
+
 impl<T: Eq + Hash + Clone> PipeT for TrackedInputPipe<T> {
     fn push_frame(&mut self) {
         self.frame_changes.push(Frame::default());
@@ -74,7 +109,7 @@ impl<T: Eq + Hash + Clone> PipeT for TrackedInputPipe<T> {
     fn pop_frame(&mut self, _commit_id: CommitId) -> Result<(), Dropped> {
         let frame = self.frame_changes.pop().unwrap();
         for (value, count) in frame.user_values {
-            self.received.values.add((value, -count));
+            self.received.undo_user(value, count);
         }
         for value in frame.seen {
             self.received.seen.remove(&value);
@@ -82,8 +117,28 @@ impl<T: Eq + Hash + Clone> PipeT for TrackedInputPipe<T> {
                 return Err(Dropped);
             }
         }
+        for value in frame.unseen {
+            self.received.seen.insert(value.clone());
+            if self.sender.send((value, ValueCount(1))).is_err() {
+                return Err(Dropped);
+            }
+        }
         Ok(())
     }
+    fn merge_frame(&mut self) {
+        let frame = self.frame_changes.pop().unwrap();
+        if let Some(parent) = self.frame_changes.last_mut() {
+            for entry in frame.user_values {
+                parent.user_values.add(entry);
+            }
+            for value in frame.seen {
+                parent.see(value);
+            }
+            for value in frame.unseen {
+                parent.unsee(value);
+            }
+        }
+    }
 }
 
 #[derive(Derivative)]
@@ -91,4 +146,19 @@ impl<T: Eq + Hash + Clone> PipeT for TrackedInputPipe<T> {
 struct Frame<T> {
     user_values: HashMap<T, ValueCount>,
     seen: HashSet<T>,
+    unseen: HashSet<T>,
+}
+
+impl<T: Eq + Hash> Frame<T> {
+    fn see(&mut self, value: T) {
+        if !self.unseen.remove(&value) {
+            self.seen.insert(value);
+        }
+    }
+
+    fn unsee(&mut self, value: T) {
+        if !self.seen.remove(&value) {
+            self.unseen.insert(value);
+        }
+    }
 }
@@ -3,11 +3,12 @@ use std::{collections::HashSet, hash::Hash};
 use crate::{
     channel,
     context::{CommitId, Dropped},
-    generic_map::AddMap,
     value_count::ValueCount,
     who::Who,
 };
 
+#[cfg(feature = "serde")]
+use super::persisted::Persist;
 use super::{values::Values, PipeT, ProcessResult, Processable};
 
 pub(crate) struct UntrackedInputPipe<T> {
@@ -15,6 +16,7 @@ pub(crate) struct UntrackedInputPipe<T> {
     sender: channel::Sender<(T, ValueCount)>,
     received: Values<T>,
     changed_keys_scratch: HashSet<T>,
+    removed_keys_scratch: HashSet<T>,
 }
 
 impl<T> UntrackedInputPipe<T> {
@@ -27,6 +29,7 @@ impl<T> UntrackedInputPipe<T> {
             sender,
             received: Values::default(),
             changed_keys_scratch: HashSet::new(),
+            removed_keys_scratch: HashSet::new(),
         }
     }
 }
@@ -36,25 +39,55 @@ impl<T: Eq + Hash + Clone> Processable for UntrackedInputPipe<T> {
         let mut result = ProcessResult::Unchanged;
         while let Some((value, who)) = self.receiver.try_recv() {
             self.changed_keys_scratch.insert(value.clone());
-            self.received.values.add((value, who.value_count()));
+            let user = matches!(who, Who::User(_));
+            if self.received.receive(value.clone(), who).0 < 0 && user {
+                self.removed_keys_scratch.insert(value);
+            }
         }
         for value in self.changed_keys_scratch.drain() {
-            if self.received.values.contains_key(&value) {
+            if self.received.is_present(&value) {
                 if self.received.seen.insert(value.clone()) {
                     result = ProcessResult::Changed;
                     if self.sender.send((value, ValueCount(1))).is_err() {
                         return Err(Dropped);
                     }
                 }
+            } else if self.removed_keys_scratch.contains(&value)
+                && self.received.seen.remove(&value)
+            {
+                result = ProcessResult::Changed;
+                if self.sender.send((value, ValueCount(-1))).is_err() {
+                    return Err(Dropped);
+                }
             }
         }
+        self.removed_keys_scratch.clear();
         Ok(result)
     }
 }
 
+#[cfg(feature = "serde")]
+impl<T> Persist for UntrackedInputPipe<T>
+where
+    T: Eq + Hash + Clone + serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn save(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self.received.save())
+    }
+    fn load(&mut self, state: serde_json::Value) -> serde_json::Result<()> {
+        let state = serde_json::from_value(state)?;
+        self.received.load(state, &mut self.sender);
+        Ok(())
+    }
+}
+
 impl<T: Eq + Hash + Clone> PipeT for UntrackedInputPipe<T> {
     fn push_frame(&mut self) {}
     fn pop_frame(&mut self, _commit_id: CommitId) -> Result<(), Dropped> {
         Ok(())
     }
+    fn merge_frame(&mut self) {}
+    fn is_frameless(&self) -> bool {
+        true
+    }
 }
@@ -1,12 +1,88 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
 
 use derivative::Derivative;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use crate::ValueCount;
+#[cfg(feature = "serde")]
+use crate::channel;
+use crate::{generic_map::AddMap, nullable::Nullable, who::Who, ValueCount};
 
 #[derive(Derivative)]
 #[derivative(Default(bound = ""))]
 pub(super) struct Values<T> {
     pub(super) values: HashMap<T, ValueCount>,
+    user: HashSet<T>,
     pub(super) seen: HashSet<T>,
 }
+
+impl<T: Eq + Hash + Clone> Values<T> {
+    pub(super) fn is_present(&self, value: &T) -> bool {
+        self.values.get(value).is_some_and(|count| count.0 > 0)
+    }
+
+    // The user holds a value at most once, so sending it twice or removing it while
+    // absent changes nothing. Returns the count actually applied.
+    pub(super) fn receive(&mut self, value: T, who: Who) -> ValueCount {
+        let count = match who {
+            Who::User(count) if count.0 > 0 => ValueCount(self.user.insert(value.clone()) as isize),
+            Who::User(count) if count.0 < 0 => ValueCount(-(self.user.remove(&value) as isize)),
+            Who::User(_) => ValueCount(0),
+            Who::Feedback(count) => count,
+        };
+        if !count.is_empty() {
+            self.values.add((value, count));
+        }
+        count
+    }
+
+    pub(super) fn undo_user(&mut self, value: T, count: ValueCount) {
+        if count.0 > 0 {
+            self.user.remove(&value);
+        } else {
+            self.user.insert(value.clone());
+        }
+        self.values.add((value, -count));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub(super) struct ValuesState<T: Eq + Hash> {
+    values: Vec<(T, ValueCount)>,
+    user: Vec<T>,
+    seen: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T: Eq + Hash + Clone> Values<T> {
+    pub(super) fn save(&self) -> ValuesState<T> {
+        ValuesState {
+            values: self
+                .values
+                .iter()
+                .map(|(value, &count)| (value.clone(), count))
+                .collect(),
+            user: self.user.iter().cloned().collect(),
+            seen: self.seen.iter().cloned().collect(),
+        }
+    }
+
+    pub(super) fn load(
+        &mut self,
+        state: ValuesState<T>,
+        sender: &mut channel::Sender<(T, ValueCount)>,
+    ) {
+        self.values = state.values.into_iter().collect();
+        self.user = state.user.into_iter().collect();
+        for value in state.seen {
+            if self.seen.insert(value.clone()) {
+                // A dropped relation is detected on the next process.
+                let _ = sender.send((value, ValueCount(1)));
+            }
+        }
+    }
+}
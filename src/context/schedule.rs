@@ -0,0 +1,138 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    sync::Arc,
+};
+
+use crate::{
+    arc_key::ArcKey,
+    graph::{SinkData, SinkKind},
+    relation::data::RelationData,
+};
+
+use super::InputId;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scheduler {
+    #[default]
+    Priority,
+    Fifo,
+}
+
+pub(crate) enum Worklist {
+    Priority(BTreeSet<usize>),
+    Fifo(VecDeque<usize>, HashSet<usize>),
+}
+
+impl Worklist {
+    pub(crate) fn new(scheduler: Scheduler) -> Self {
+        match scheduler {
+            Scheduler::Priority => Worklist::Priority(BTreeSet::new()),
+            Scheduler::Fifo => Worklist::Fifo(VecDeque::new(), HashSet::new()),
+        }
+    }
+
+    pub(crate) fn push(&mut self, sink: usize) {
+        match self {
+            Worklist::Priority(set) => {
+                set.insert(sink);
+            }
+            Worklist::Fifo(queue, queued) => {
+                if queued.insert(sink) {
+                    queue.push_back(sink);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<usize> {
+        match self {
+            Worklist::Priority(set) => set.pop_first(),
+            Worklist::Fifo(queue, queued) => {
+                let sink = queue.pop_front()?;
+                queued.remove(&sink);
+                Some(sink)
+            }
+        }
+    }
+}
+
+#[allow(clippy::mutable_key_type)]
+pub(crate) fn sink_inputs(sinks: &[SinkData]) -> Vec<Vec<InputId>> {
+    sinks
+        .iter()
+        .map(|SinkData { relation, .. }| {
+            let mut visited = HashSet::new();
+            let mut inputs = HashSet::new();
+            collect_inputs(relation, &mut visited, &mut inputs);
+            inputs.into_iter().collect()
+        })
+        .collect()
+}
+
+pub(crate) fn dependents(
+    sink_inputs: &[Vec<InputId>],
+    feedback_sinks: impl IntoIterator<Item = usize>,
+) -> HashMap<InputId, Vec<usize>> {
+    let mut result = HashMap::<InputId, Vec<usize>>::new();
+    for sink in feedback_sinks {
+        for &input_id in sink_inputs[sink].iter() {
+            result.entry(input_id).or_default().push(sink);
+        }
+    }
+    result
+}
+
+// A sink is suspended if it is inactive, or if it is a feedback pipe that only
+// inactive sinks depend on.
+pub(crate) fn suspended(
+    sinks: &[SinkData],
+    sink_inputs: &[Vec<InputId>],
+    inactive: &HashSet<usize>,
+) -> HashSet<usize> {
+    let mut feeders = HashMap::<InputId, Vec<usize>>::new();
+    for (sink, SinkData { kind, .. }) in sinks.iter().enumerate() {
+        if let SinkKind::Feedback(input_id) = kind {
+            feeders.entry(*input_id).or_default().push(sink);
+        }
+    }
+    let upstream = |roots: Vec<usize>| {
+        let mut result = HashSet::new();
+        let mut stack = roots;
+        while let Some(sink) = stack.pop() {
+            if result.insert(sink) {
+                for input_id in sink_inputs[sink].iter() {
+                    stack.extend(feeders.get(input_id).into_iter().flatten());
+                }
+            }
+        }
+        result
+    };
+    let demanded = upstream(
+        (0..sinks.len())
+            .filter(|sink| {
+                !inactive.contains(sink) && !matches!(sinks[*sink].kind, SinkKind::Feedback(_))
+            })
+            .collect(),
+    );
+    upstream(inactive.iter().copied().collect())
+        .into_iter()
+        .filter(|sink| inactive.contains(sink) || !demanded.contains(sink))
+        .collect()
+}
+
+#[allow(clippy::mutable_key_type)]
+fn collect_inputs(
+    data: &Arc<RelationData>,
+    visited: &mut HashSet<ArcKey<RelationData>>,
+    inputs: &mut HashSet<InputId>,
+) {
+    if !visited.insert(ArcKey(data.clone())) {
+        return;
+    }
+    if let Some(input_id) = data.input_id {
+        inputs.insert(input_id);
+    }
+    for child in data.children.iter() {
+        collect_inputs(child, visited, inputs);
+    }
+}
@@ -0,0 +1,241 @@
+use std::{collections::HashMap, error::Error, fmt, panic::Location, sync::Arc};
+
+use crate::{
+    arc_key::ArcKey,
+    context::InputId,
+    graph::{SinkData, SinkKind},
+    relation::data::RelationData,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CycleCheck {
+    #[default]
+    Ignore,
+    /// Logs each cycle with `log::warn!`, or prints it to stderr without the `log`
+    /// feature.
+    Warn,
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CycleKind {
+    NonMonotone,
+    Reduce,
+}
+
+#[derive(Clone, Debug)]
+pub struct CycleRelation {
+    pub name: Option<String>,
+    pub type_name: &'static str,
+    pub location: &'static Location<'static>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FeedbackCycle {
+    pub kind: CycleKind,
+    pub operator: CycleRelation,
+    pub relations: Vec<CycleRelation>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CycleError(pub Vec<FeedbackCycle>);
+
+impl FeedbackCycle {
+    pub(crate) fn find_all(sinks: &[SinkData]) -> Vec<Self> {
+        let mut graph = Graph::default();
+        let mut feedback_edges = Vec::new();
+        for SinkData { kind, relation } in sinks {
+            let from = graph.visit(relation);
+            if let SinkKind::Feedback(input_id) = kind {
+                feedback_edges.push((from, *input_id));
+            }
+        }
+        for (from, input_id) in feedback_edges {
+            if let Some(&to) = graph.inputs.get(&input_id) {
+                graph.successors[from].push(to);
+            }
+        }
+        let mut result = Vec::new();
+        for component in Tarjan::components(&graph.successors) {
+            let is_cycle = component.len() > 1 || {
+                let node = component[0];
+                graph.successors[node].contains(&node)
+            };
+            if !is_cycle {
+                continue;
+            }
+            let relations = component
+                .iter()
+                .map(|&node| &graph.nodes[node])
+                .filter(|data| !data.hidden)
+                .map(|data| CycleRelation {
+                    name: data.name.clone(),
+                    type_name: data.type_name,
+                    location: data.location,
+                })
+                .collect::<Vec<_>>();
+            for &node in component.iter() {
+                let data = &graph.nodes[node];
+                if let Some(kind) = CycleKind::of(data) {
+                    result.push(FeedbackCycle {
+                        kind,
+                        operator: CycleRelation {
+                            name: data.name.clone(),
+                            type_name: data.op_type_name,
+                            location: data.location,
+                        },
+                        relations: relations.clone(),
+                    });
+                }
+            }
+        }
+        result
+    }
+}
+
+impl CycleKind {
+    fn of(data: &RelationData) -> Option<Self> {
+        match data.op_type_name {
+            "negate" | "antijoin" | "left_join" | "outer_join" => Some(CycleKind::NonMonotone),
+            // `distinct` is built on `reduce` but only ever adds or removes whole keys.
+            "reduce" | "par_reduce" if data.type_name != "distinct" => Some(CycleKind::Reduce),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Graph {
+    nodes: Vec<Arc<RelationData>>,
+    successors: Vec<Vec<usize>>,
+    indices: HashMap<ArcKey<RelationData>, usize>,
+    inputs: HashMap<InputId, usize>,
+}
+
+impl Graph {
+    fn visit(&mut self, data: &Arc<RelationData>) -> usize {
+        if let Some(&index) = self.indices.get(&ArcKey(data.clone())) {
+            return index;
+        }
+        let index = self.nodes.len();
+        self.nodes.push(data.clone());
+        self.successors.push(Vec::new());
+        self.indices.insert(ArcKey(data.clone()), index);
+        if let Some(input_id) = data.input_id {
+            self.inputs.insert(input_id, index);
+        }
+        for child in data.children.iter() {
+            let child_index = self.visit(child);
+            self.successors[child_index].push(index);
+        }
+        index
+    }
+}
+
+struct Tarjan<'a> {
+    successors: &'a [Vec<usize>],
+    next_index: usize,
+    indices: Vec<Option<usize>>,
+    lowlinks: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    components: Vec<Vec<usize>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn components(successors: &'a [Vec<usize>]) -> Vec<Vec<usize>> {
+        let mut tarjan = Tarjan {
+            successors,
+            next_index: 0,
+            indices: vec![None; successors.len()],
+            lowlinks: vec![0; successors.len()],
+            on_stack: vec![false; successors.len()],
+            stack: Vec::new(),
+            components: Vec::new(),
+        };
+        for node in 0..successors.len() {
+            if tarjan.indices[node].is_none() {
+                tarjan.connect(node);
+            }
+        }
+        tarjan.components
+    }
+
+    fn connect(&mut self, node: usize) {
+        let index = self.next_index;
+        self.next_index += 1;
+        self.indices[node] = Some(index);
+        self.lowlinks[node] = index;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+        for &next in self.successors[node].iter() {
+            match self.indices[next] {
+                None => {
+                    self.connect(next);
+                    self.lowlinks[node] = self.lowlinks[node].min(self.lowlinks[next]);
+                }
+                Some(next_index) if self.on_stack[next] => {
+                    self.lowlinks[node] = self.lowlinks[node].min(next_index);
+                }
+                Some(_) => {}
+            }
+        }
+        if self.lowlinks[node] == index {
+            let mut component = Vec::new();
+            loop {
+                let member = self.stack.pop().unwrap();
+                self.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            component.sort();
+            self.components.push(component);
+        }
+    }
+}
+
+impl fmt::Display for CycleRelation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} ({}) at {}", name, self.type_name, self.location),
+            None => write!(f, "{} at {}", self.type_name, self.location),
+        }
+    }
+}
+
+impl fmt::Display for FeedbackCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            CycleKind::NonMonotone => "non-monotone",
+            CycleKind::Reduce => "reducing",
+        };
+        write!(
+            f,
+            "{} operator {} in feedback cycle through ",
+            kind, self.operator
+        )?;
+        for (i, relation) in self.relations.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", relation)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, cycle) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", cycle)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for CycleError {}
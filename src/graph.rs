@@ -0,0 +1,140 @@
+use std::{collections::HashMap, fmt::Write, sync::Arc};
+
+use crate::{
+    arc_key::ArcKey,
+    context::{InputId, InterruptId},
+    relation::data::RelationData,
+};
+
+pub(crate) enum SinkKind {
+    Output,
+    Feedback(InputId),
+    Interrupt(InterruptId),
+    Subscription,
+    Sink,
+    Constraint(String),
+    #[cfg(feature = "redis")]
+    Redis(String),
+}
+
+pub(crate) struct SinkData {
+    pub(crate) kind: SinkKind,
+    pub(crate) relation: Arc<RelationData>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Relation,
+    Sink,
+}
+
+#[derive(Clone, Debug)]
+pub struct DataflowNode {
+    pub name: Option<String>,
+    pub type_name: &'static str,
+    pub kind: NodeKind,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DataflowGraph {
+    pub nodes: Vec<DataflowNode>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl DataflowGraph {
+    pub(crate) fn new(sinks: &[SinkData]) -> Self {
+        let mut builder = Builder::default();
+        let mut feedback_edges = Vec::new();
+        for SinkData { kind, relation } in sinks {
+            let from = builder.visit(relation);
+            let (name, type_name) = match kind {
+                SinkKind::Output => (None, "output"),
+                SinkKind::Feedback(input_id) => {
+                    feedback_edges.push((builder.graph.nodes.len(), *input_id));
+                    (None, "feedback")
+                }
+                SinkKind::Interrupt(interrupt_id) => (Some(interrupt_id.to_string()), "interrupt"),
+                SinkKind::Subscription => (None, "subscribe"),
+                SinkKind::Sink => (None, "sink"),
+                SinkKind::Constraint(name) => (Some(name.clone()), "constraint"),
+                #[cfg(feature = "redis")]
+                SinkKind::Redis(name) => (Some(name.clone()), "redis"),
+            };
+            let to = builder.push(DataflowNode {
+                name,
+                type_name,
+                kind: NodeKind::Sink,
+            });
+            builder.graph.edges.push((from, to));
+        }
+        for (from, input_id) in feedback_edges {
+            if let Some(&to) = builder.inputs.get(&input_id) {
+                builder.graph.edges.push((from, to));
+            }
+        }
+        builder.graph
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut result = String::from("digraph dataflow {\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let label = match &node.name {
+                Some(name) => format!("{}\n({})", name, node.type_name),
+                None => node.type_name.to_string(),
+            };
+            let shape = match node.kind {
+                NodeKind::Relation => "ellipse",
+                NodeKind::Sink => "box",
+            };
+            writeln!(result, "    n{} [label={:?}, shape={}];", i, label, shape).unwrap();
+        }
+        for (from, to) in self.edges.iter() {
+            writeln!(result, "    n{} -> n{};", from, to).unwrap();
+        }
+        result.push_str("}\n");
+        result
+    }
+}
+
+#[derive(Default)]
+struct Builder {
+    graph: DataflowGraph,
+    indices: HashMap<ArcKey<RelationData>, usize>,
+    inputs: HashMap<InputId, usize>,
+}
+
+impl Builder {
+    fn push(&mut self, node: DataflowNode) -> usize {
+        self.graph.nodes.push(node);
+        self.graph.nodes.len() - 1
+    }
+
+    fn visit(&mut self, data: &Arc<RelationData>) -> usize {
+        let data = representative(data);
+        if let Some(&index) = self.indices.get(&ArcKey(data.clone())) {
+            return index;
+        }
+        let index = self.push(DataflowNode {
+            name: data.name.clone(),
+            type_name: data.type_name,
+            kind: NodeKind::Relation,
+        });
+        self.indices.insert(ArcKey(data.clone()), index);
+        if let Some(input_id) = data.input_id {
+            self.inputs.insert(input_id, index);
+        }
+        for child in data.children.iter() {
+            let child_index = self.visit(child);
+            self.graph.edges.push((child_index, index));
+        }
+        index
+    }
+}
+
+pub(crate) fn representative(data: &Arc<RelationData>) -> &Arc<RelationData> {
+    if data.hidden && data.children.len() == 1 {
+        representative(&data.children[0])
+    } else {
+        data
+    }
+}
@@ -1,23 +1,45 @@
-pub use self::context::{CreationContext, ExecutionContext, InterruptId};
+pub use self::context::{
+    CommitError, CommitId, CommitLimits, ConstraintHandle, CreationContext, ExecutionContext,
+    FrameId, InterruptHandle, InterruptId, PipeInfo, Scheduler, SinkId, TransactionError,
+    ViolatingTuples,
+};
+#[cfg(feature = "redis")]
+pub use self::context::{RedisEncoding, RedisLayout, RedisRetry};
+pub use self::cycles::{CycleCheck, CycleError, CycleKind, CycleRelation, FeedbackCycle};
 pub use self::generic_map::SingletonMap;
+pub use self::graph::{DataflowGraph, DataflowNode, NodeKind};
+pub use self::op::Op;
 pub use self::operators::{
+    arrange::Arranged,
     input::{Input, InputRelation},
     save::Saved,
+    variable::{Variable, VariableFeed},
 };
 pub use self::output::{Output, SavedOutput};
-pub use self::relation::Relation;
+pub use self::profile::{Profile, RelationProfile};
+pub use self::relation::{args::RelationArgs, Relation, RelationInfo, RelationInner};
+pub use self::sink::{Sink, SinkError};
+#[cfg(feature = "serde")]
+pub use self::snapshot::{Snapshot, SnapshotError};
 pub use self::value_count::ValueCount;
 
 mod arc_key;
 mod broadcast_channel;
 mod channel;
 mod context;
+mod cycles;
 mod entry;
 mod generic_map;
+mod graph;
 mod nullable;
 mod op;
 mod operators;
 mod output;
+mod profile;
 mod relation;
+pub mod search;
+mod sink;
+#[cfg(feature = "serde")]
+mod snapshot;
 mod value_count;
 mod who;
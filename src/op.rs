@@ -7,6 +7,13 @@ use crate::{
 
 pub trait Op<T> {
     fn type_name(&self) -> &'static str;
+    /// Emits the changes to this relation since the previous call as `(tuple, count)` deltas.
+    ///
+    /// Implementations must pull each upstream `RelationInner` with the same `current_id` and
+    /// must not emit anything that was not caused by upstream changes, so that a call with no
+    /// upstream changes emits nothing. Deltas need not be consolidated, but their sum over all
+    /// calls must equal the current contents of the relation. `current_id` increases between
+    /// passes; a shared upstream pulled twice with the same id yields its changes only once.
     fn foreach<F: FnMut(T, ValueCount)>(&mut self, current_id: CommitId, f: F);
     fn dump_to_map(
         &mut self,
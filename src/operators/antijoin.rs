@@ -0,0 +1,68 @@
+use std::{collections::HashMap, hash::Hash};
+
+use generic_map::rollover_map::RolloverMap;
+
+use crate::{
+    context::CommitId, generic_map::AddMap, op::Op, relation::RelationInner,
+    value_count::ValueCount,
+};
+
+pub struct AntiJoin<K, V, CL, CR> {
+    left_rel: RelationInner<(K, V), CL>,
+    right_rel: RelationInner<K, CR>,
+    left_values: HashMap<K, RolloverMap<V, ValueCount, 2>>,
+    right_counts: HashMap<K, ValueCount>,
+}
+
+impl<K, V, CL, CR> AntiJoin<K, V, CL, CR> {
+    pub(crate) fn new(
+        (left_rel, right_rel): (RelationInner<(K, V), CL>, RelationInner<K, CR>),
+    ) -> Self {
+        Self {
+            left_rel,
+            right_rel,
+            left_values: HashMap::default(),
+            right_counts: HashMap::default(),
+        }
+    }
+}
+
+// A key only blocks the left side while its right count is positive.
+fn is_present<K: Eq + Hash>(right_counts: &HashMap<K, ValueCount>, k: &K) -> bool {
+    right_counts.get(k).is_some_and(|count| count.0 > 0)
+}
+
+impl<K, V, CL, CR> Op<(K, V)> for AntiJoin<K, V, CL, CR>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+    CL: Op<(K, V)>,
+    CR: Op<K>,
+{
+    fn type_name(&self) -> &'static str {
+        "antijoin"
+    }
+    fn foreach<F: FnMut((K, V), ValueCount)>(&mut self, current_id: CommitId, mut f: F) {
+        self.left_rel.foreach(current_id, |(k, v), count| {
+            if !is_present(&self.right_counts, &k) {
+                f((k.clone(), v.clone()), count)
+            }
+            self.left_values.add((k, (v, count)));
+        });
+        self.right_rel.foreach(current_id, |k, count| {
+            let was_present = is_present(&self.right_counts, &k);
+            self.right_counts.add((k.clone(), count));
+            if was_present == is_present(&self.right_counts, &k) {
+                return;
+            }
+            let sign = if was_present {
+                ValueCount(1)
+            } else {
+                ValueCount(-1)
+            };
+            for (v, &lcount) in self.left_values.get(&k).into_iter().flatten() {
+                f((k.clone(), v.clone()), lcount * sign)
+            }
+        });
+    }
+}
@@ -0,0 +1,330 @@
+#![allow(clippy::type_complexity)]
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    rc::Rc,
+    sync::Arc,
+};
+
+use generic_map::rollover_map::RolloverMap;
+
+use crate::{
+    broadcast_channel::{Receiver, Sender},
+    context::{CommitId, ContextId},
+    entry::Entry,
+    generic_map::AddMap,
+    op::{DynOp, Op},
+    relation::{args::Sealed, data::RelationData, Relation, RelationInner},
+    value_count::ValueCount,
+};
+
+use super::{consolidate::Consolidate, reduce::update_output};
+
+struct ArrangedInner<K, V, C> {
+    context_id: ContextId,
+    data: Arc<RelationData>,
+    last_id: CommitId,
+    sub_rel: RelationInner<(K, V), C>,
+    index: HashMap<K, RolloverMap<V, ValueCount, 2>>,
+    sender: Sender<((K, V), ValueCount)>,
+}
+
+impl<K, V, C> ArrangedInner<K, V, C>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+    C: Op<(K, V)>,
+{
+    fn update(&mut self, current_id: CommitId) {
+        if self.last_id < current_id {
+            let Self {
+                sub_rel,
+                index,
+                sender,
+                ..
+            } = self;
+            sub_rel.foreach(current_id, |(k, v), count| {
+                sender.send(&((k.clone(), v.clone()), count));
+                index.add((k, (v, count)));
+            });
+            sender.consolidate();
+            self.last_id = current_id
+        }
+    }
+}
+
+pub struct Arranged<K, V, C = Box<dyn DynOp<(K, V)>>>(Rc<RefCell<ArrangedInner<K, V, C>>>);
+
+impl<K, V, C> Arranged<K, V, C> {
+    pub(crate) fn new(sub_rel: Relation<(K, V), C>) -> Self {
+        Self(Rc::new(RefCell::new(ArrangedInner {
+            context_id: sub_rel.context_id,
+            data: Arc::new(sub_rel.data),
+            last_id: CommitId::default(),
+            sub_rel: sub_rel.inner,
+            index: HashMap::new(),
+            sender: Sender::new(),
+        })))
+    }
+
+    fn subscribe(&self) -> Subscription<K, V, C> {
+        let receiver = self.0.borrow_mut().sender.subscribe();
+        Subscription {
+            inner: self.0.clone(),
+            receiver,
+        }
+    }
+}
+
+struct Subscription<K, V, C> {
+    inner: Rc<RefCell<ArrangedInner<K, V, C>>>,
+    receiver: Receiver<((K, V), ValueCount)>,
+}
+
+impl<K, V, C> Subscription<K, V, C>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+    C: Op<(K, V)>,
+{
+    fn pull(&mut self, current_id: CommitId) {
+        self.inner.borrow_mut().update(current_id)
+    }
+}
+
+impl<K, V, C> Sealed for Subscription<K, V, C> {
+    type Inner = Self;
+
+    fn add_context_ids(&self, s: &mut RolloverMap<ContextId, ValueCount>) {
+        s.add((self.inner.borrow().context_id, ValueCount(1)));
+    }
+    fn push_datas(self, v: &mut Vec<Arc<RelationData>>) -> Self::Inner {
+        v.push(self.inner.borrow().data.clone());
+        self
+    }
+}
+
+impl<K, V, C> Arranged<K, V, C>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+    C: Op<(K, V)>,
+{
+    #[track_caller]
+    pub fn get(&self) -> Relation<(K, V), ArrangedOp<K, V, C>> {
+        Relation::from_op(self.subscribe(), ArrangedOp).hidden()
+    }
+
+    #[track_caller]
+    pub fn join<VR, CR>(
+        &self,
+        other: Relation<(K, VR), CR>,
+    ) -> Relation<(K, V, VR), Consolidate<(K, V, VR), ArrangedJoin<K, V, C, VR, CR>>>
+    where
+        VR: Eq + Hash + Clone,
+        CR: Op<(K, VR)>,
+    {
+        Relation::from_op((self.subscribe(), other), ArrangedJoin::new).consolidate_h()
+    }
+
+    #[track_caller]
+    pub fn semijoin(&self, other: Relation<K, impl Op<K>>) -> Relation<(K, V), impl Op<(K, V)>> {
+        self.join(other.map_h(|t| (t, ())))
+            .map_h(|(k, v, ())| (k, v))
+            .type_named("semijoin")
+    }
+
+    #[track_caller]
+    pub fn antijoin<CR: Op<K>>(
+        &self,
+        other: Relation<K, CR>,
+    ) -> Relation<(K, V), Consolidate<(K, V), ArrangedAntiJoin<K, V, C, CR>>> {
+        Relation::from_op((self.subscribe(), other), ArrangedAntiJoin::new).consolidate_h()
+    }
+
+    #[track_caller]
+    pub fn reduce<Y, G: Fn(&K, &RolloverMap<V, ValueCount, 2>) -> Y>(
+        &self,
+        g: G,
+    ) -> Relation<(K, Y), Consolidate<(K, Y), ArrangedReduce<K, V, Y, G, C>>>
+    where
+        Y: Eq + Hash + Clone,
+    {
+        Relation::from_op(self.subscribe(), |sub| ArrangedReduce::new(sub, g)).consolidate_h()
+    }
+}
+
+pub struct ArrangedOp<K, V, C>(Subscription<K, V, C>);
+
+impl<K, V, C> Op<(K, V)> for ArrangedOp<K, V, C>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+    C: Op<(K, V)>,
+{
+    fn type_name(&self) -> &'static str {
+        "arranged"
+    }
+    fn foreach<F: FnMut((K, V), ValueCount)>(&mut self, current_id: CommitId, mut f: F) {
+        self.0.pull(current_id);
+        while let Some((kv, count)) = self.0.receiver.try_recv() {
+            f(kv, count)
+        }
+    }
+}
+
+pub struct ArrangedJoin<K, VL, CL, VR, CR> {
+    left: Subscription<K, VL, CL>,
+    right_rel: RelationInner<(K, VR), CR>,
+    right_values: HashMap<K, RolloverMap<VR, ValueCount, 2>>,
+    right_changes_scratch: Vec<Entry<(K, VR)>>,
+}
+
+impl<K, VL, CL, VR, CR> ArrangedJoin<K, VL, CL, VR, CR> {
+    fn new((left, right_rel): (Subscription<K, VL, CL>, RelationInner<(K, VR), CR>)) -> Self {
+        Self {
+            left,
+            right_rel,
+            right_values: HashMap::default(),
+            right_changes_scratch: Vec::new(),
+        }
+    }
+}
+
+impl<K, VL, CL, VR, CR> Op<(K, VL, VR)> for ArrangedJoin<K, VL, CL, VR, CR>
+where
+    K: Eq + Hash + Clone,
+    VL: Eq + Hash + Clone,
+    VR: Eq + Hash + Clone,
+    CL: Op<(K, VL)>,
+    CR: Op<(K, VR)>,
+{
+    fn type_name(&self) -> &'static str {
+        "join"
+    }
+    fn foreach<F: FnMut((K, VL, VR), ValueCount)>(&mut self, current_id: CommitId, mut f: F) {
+        self.left.pull(current_id);
+        while let Some(((k, vl), lcount)) = self.left.receiver.try_recv() {
+            for (vr, &rcount) in self.right_values.get(&k).into_iter().flatten() {
+                f((k.clone(), vl.clone(), vr.clone()), lcount * rcount)
+            }
+        }
+        self.right_rel
+            .dump_to_vec(current_id, &mut self.right_changes_scratch);
+        let left = self.left.inner.borrow();
+        for e in self.right_changes_scratch.drain(..) {
+            let Entry {
+                value: (k, vr),
+                value_count: rcount,
+            } = e;
+            for (vl, &lcount) in left.index.get(&k).into_iter().flatten() {
+                f((k.clone(), vl.clone(), vr.clone()), lcount * rcount)
+            }
+            self.right_values.add((k, (vr, rcount)));
+        }
+    }
+}
+
+pub struct ArrangedAntiJoin<K, V, CL, CR> {
+    left: Subscription<K, V, CL>,
+    right_rel: RelationInner<K, CR>,
+    right_counts: HashMap<K, ValueCount>,
+    right_changes_scratch: Vec<Entry<K>>,
+}
+
+impl<K, V, CL, CR> ArrangedAntiJoin<K, V, CL, CR> {
+    fn new((left, right_rel): (Subscription<K, V, CL>, RelationInner<K, CR>)) -> Self {
+        Self {
+            left,
+            right_rel,
+            right_counts: HashMap::default(),
+            right_changes_scratch: Vec::new(),
+        }
+    }
+}
+
+impl<K, V, CL, CR> Op<(K, V)> for ArrangedAntiJoin<K, V, CL, CR>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+    CL: Op<(K, V)>,
+    CR: Op<K>,
+{
+    fn type_name(&self) -> &'static str {
+        "antijoin"
+    }
+    fn foreach<F: FnMut((K, V), ValueCount)>(&mut self, current_id: CommitId, mut f: F) {
+        self.left.pull(current_id);
+        while let Some(((k, v), count)) = self.left.receiver.try_recv() {
+            if !self.right_counts.contains_key(&k) {
+                f((k, v), count)
+            }
+        }
+        self.right_rel
+            .dump_to_vec(current_id, &mut self.right_changes_scratch);
+        let left = self.left.inner.borrow();
+        for e in self.right_changes_scratch.drain(..) {
+            let Entry {
+                value: k,
+                value_count: count,
+            } = e;
+            let was_present = self.right_counts.contains_key(&k);
+            self.right_counts.add((k.clone(), count));
+            if was_present == self.right_counts.contains_key(&k) {
+                continue;
+            }
+            let sign = if was_present {
+                ValueCount(1)
+            } else {
+                ValueCount(-1)
+            };
+            for (v, &lcount) in left.index.get(&k).into_iter().flatten() {
+                f((k.clone(), v.clone()), lcount * sign)
+            }
+        }
+    }
+}
+
+pub struct ArrangedReduce<K, V, Y, G, C> {
+    left: Subscription<K, V, C>,
+    g: G,
+    outputs: HashMap<K, Y>,
+    changed_keys_scratch: HashSet<K>,
+}
+
+impl<K, V, Y, G, C> ArrangedReduce<K, V, Y, G, C> {
+    fn new(left: Subscription<K, V, C>, g: G) -> Self {
+        Self {
+            left,
+            g,
+            outputs: HashMap::new(),
+            changed_keys_scratch: HashSet::new(),
+        }
+    }
+}
+
+impl<K, V, Y, G, C> Op<(K, Y)> for ArrangedReduce<K, V, Y, G, C>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+    Y: Eq + Clone,
+    G: Fn(&K, &RolloverMap<V, ValueCount, 2>) -> Y,
+    C: Op<(K, V)>,
+{
+    fn type_name(&self) -> &'static str {
+        "reduce"
+    }
+    fn foreach<F: FnMut((K, Y), ValueCount)>(&mut self, current_id: CommitId, mut f: F) {
+        self.left.pull(current_id);
+        while let Some(((k, _), _)) = self.left.receiver.try_recv() {
+            self.changed_keys_scratch.insert(k);
+        }
+        let left = self.left.inner.borrow();
+        for k in self.changed_keys_scratch.drain() {
+            let new_y = left.index.get(&k).map(|vals| (self.g)(&k, vals));
+            update_output(&mut self.outputs, k, new_y, &mut f);
+        }
+    }
+}
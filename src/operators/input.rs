@@ -2,7 +2,7 @@ use derivative::Derivative;
 
 use crate::{
     channel::{Receiver, Sender},
-    context::{CommitId, ContextId},
+    context::{CommitId, ContextId, InputId},
     op::Op,
     relation::Relation,
     value_count::ValueCount,
@@ -13,14 +13,34 @@ use crate::{
 #[derivative(Clone(bound = ""))]
 pub struct Input<T> {
     pub(crate) context_id: ContextId,
+    pub(crate) input_id: InputId,
+    frameless: bool,
     sender: Sender<(T, Who)>,
 }
 
 pub type InputRelation<T> = Relation<T, InputOp<T>>;
 
 impl<T> Input<T> {
-    pub(crate) fn new(context_id: ContextId, sender: Sender<(T, Who)>) -> Self {
-        Self { context_id, sender }
+    pub(crate) fn new(
+        context_id: ContextId,
+        input_id: InputId,
+        frameless: bool,
+        sender: Sender<(T, Who)>,
+    ) -> Self {
+        Self {
+            context_id,
+            input_id,
+            frameless,
+            sender,
+        }
+    }
+
+    pub fn is_frameless(&self) -> bool {
+        self.frameless
+    }
+
+    pub(crate) fn is_connected(&self) -> bool {
+        self.sender.is_connected()
     }
 
     pub(crate) fn send_count(&mut self, elem: T, who: Who) -> Result<(), T> {
@@ -28,7 +48,18 @@ impl<T> Input<T> {
     }
 
     pub fn send(&mut self, elem: T) -> Result<(), T> {
-        self.send_count(elem, Who::User)
+        self.update(elem, ValueCount(1))
+    }
+
+    pub fn remove(&mut self, elem: T) -> Result<(), T> {
+        self.update(elem, ValueCount(-1))
+    }
+
+    /// Adds `count` copies of `elem` to a bag input, or removes them if `count` is
+    /// negative. Inputs from `input` are sets and only look at the sign: a positive
+    /// count adds `elem` once, a negative one removes it and zero does nothing.
+    pub fn update(&mut self, elem: T, count: ValueCount) -> Result<(), T> {
+        self.send_count(elem, Who::User(count))
     }
 }
 
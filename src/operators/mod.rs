@@ -1,9 +1,17 @@
+pub mod antijoin;
+pub mod arrange;
 pub mod concat;
 pub mod consolidate;
 pub mod flat_map;
 pub mod input;
 pub mod join;
 pub mod negate;
+pub mod outer_join;
+#[cfg(feature = "parallel")]
+pub mod par_join;
+#[cfg(feature = "parallel")]
+pub mod par_reduce;
 pub mod reduce;
 pub mod save;
 pub mod split;
+pub mod variable;
@@ -0,0 +1,112 @@
+#![allow(clippy::type_complexity)]
+
+use std::{collections::HashMap, hash::Hash};
+
+use generic_map::rollover_map::RolloverMap;
+
+use crate::{
+    context::CommitId, generic_map::AddMap, op::Op, relation::RelationInner,
+    value_count::ValueCount,
+};
+
+pub struct OuterJoin<K, VL, CL, VR, CR> {
+    left_rel: RelationInner<(K, VL), CL>,
+    right_rel: RelationInner<(K, VR), CR>,
+    left_values: HashMap<K, RolloverMap<VL, ValueCount, 2>>,
+    right_values: HashMap<K, RolloverMap<VR, ValueCount, 2>>,
+    keep_unmatched_right: bool,
+}
+
+impl<K, VL, CL, VR, CR> OuterJoin<K, VL, CL, VR, CR> {
+    pub(crate) fn new(
+        (left_rel, right_rel): (RelationInner<(K, VL), CL>, RelationInner<(K, VR), CR>),
+        keep_unmatched_right: bool,
+    ) -> Self {
+        Self {
+            left_rel,
+            right_rel,
+            left_values: HashMap::default(),
+            right_values: HashMap::default(),
+            keep_unmatched_right,
+        }
+    }
+}
+
+impl<K, VL, CL, VR, CR> Op<(K, Option<VL>, Option<VR>)> for OuterJoin<K, VL, CL, VR, CR>
+where
+    K: Eq + Hash + Clone,
+    VL: Eq + Hash + Clone,
+    VR: Eq + Hash + Clone,
+    CL: Op<(K, VL)>,
+    CR: Op<(K, VR)>,
+{
+    fn type_name(&self) -> &'static str {
+        if self.keep_unmatched_right {
+            "outer_join"
+        } else {
+            "left_join"
+        }
+    }
+    fn foreach<F: FnMut((K, Option<VL>, Option<VR>), ValueCount)>(
+        &mut self,
+        current_id: CommitId,
+        mut f: F,
+    ) {
+        self.left_rel.foreach(current_id, |(k, vl), lcount| {
+            match self.right_values.get(&k) {
+                Some(rvals) => {
+                    for (vr, &rcount) in rvals {
+                        f(
+                            (k.clone(), Some(vl.clone()), Some(vr.clone())),
+                            lcount * rcount,
+                        )
+                    }
+                }
+                None => f((k.clone(), Some(vl.clone()), None), lcount),
+            }
+            let was_present = self.left_values.contains_key(&k);
+            self.left_values.add((k.clone(), (vl, lcount)));
+            if !self.keep_unmatched_right || was_present == self.left_values.contains_key(&k) {
+                return;
+            }
+            let sign = if was_present {
+                ValueCount(1)
+            } else {
+                ValueCount(-1)
+            };
+            for (vr, &rcount) in self.right_values.get(&k).into_iter().flatten() {
+                f((k.clone(), None, Some(vr.clone())), rcount * sign)
+            }
+        });
+        self.right_rel.foreach(current_id, |(k, vr), rcount| {
+            match self.left_values.get(&k) {
+                Some(lvals) => {
+                    for (vl, &lcount) in lvals {
+                        f(
+                            (k.clone(), Some(vl.clone()), Some(vr.clone())),
+                            lcount * rcount,
+                        )
+                    }
+                }
+                None => {
+                    if self.keep_unmatched_right {
+                        f((k.clone(), None, Some(vr.clone())), rcount)
+                    }
+                }
+            }
+            let was_present = self.right_values.contains_key(&k);
+            self.right_values.add((k.clone(), (vr, rcount)));
+            if was_present == self.right_values.contains_key(&k) {
+                return;
+            }
+            let sign = if was_present {
+                ValueCount(1)
+            } else {
+                ValueCount(-1)
+            };
+            for (vl, &lcount) in self.left_values.get(&k).into_iter().flatten() {
+                f((k.clone(), Some(vl.clone()), None), lcount * sign)
+            }
+        });
+    }
+}
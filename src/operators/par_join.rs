@@ -0,0 +1,114 @@
+#![allow(clippy::type_complexity)]
+
+use std::{collections::HashMap, hash::Hash};
+
+use generic_map::rollover_map::RolloverMap;
+use rayon::prelude::*;
+
+use crate::{
+    context::CommitId, entry::Entry, generic_map::AddMap, op::Op, relation::RelationInner,
+    value_count::ValueCount,
+};
+
+pub struct ParJoin<K, VL, CL, VR, CR> {
+    left_rel: RelationInner<(K, VL), CL>,
+    right_rel: RelationInner<(K, VR), CR>,
+    left_values: HashMap<K, RolloverMap<VL, ValueCount, 2>>,
+    right_values: HashMap<K, RolloverMap<VR, ValueCount, 2>>,
+    left_changes_scratch: Vec<Entry<(K, VL)>>,
+    right_changes_scratch: Vec<Entry<(K, VR)>>,
+}
+
+impl<K, VL, CL, VR, CR> ParJoin<K, VL, CL, VR, CR> {
+    pub(crate) fn new(
+        (left_rel, right_rel): (RelationInner<(K, VL), CL>, RelationInner<(K, VR), CR>),
+    ) -> Self {
+        Self {
+            left_rel,
+            right_rel,
+            left_values: HashMap::default(),
+            right_values: HashMap::default(),
+            left_changes_scratch: Vec::new(),
+            right_changes_scratch: Vec::new(),
+        }
+    }
+}
+
+impl<K, VL, CL, VR, CR> Op<(K, VL, VR)> for ParJoin<K, VL, CL, VR, CR>
+where
+    K: Eq + Hash + Clone + Send + Sync,
+    VL: Eq + Hash + Clone + Send + Sync,
+    VR: Eq + Hash + Clone + Send + Sync,
+    CL: Op<(K, VL)>,
+    CR: Op<(K, VR)>,
+{
+    fn type_name(&self) -> &'static str {
+        "par_join"
+    }
+    fn foreach<F: FnMut((K, VL, VR), ValueCount)>(&mut self, current_id: CommitId, mut f: F) {
+        self.left_rel
+            .dump_to_vec(current_id, &mut self.left_changes_scratch);
+        self.right_rel
+            .dump_to_vec(current_id, &mut self.right_changes_scratch);
+
+        let right_values = &self.right_values;
+        let outputs = self
+            .left_changes_scratch
+            .par_iter()
+            .flat_map_iter(
+                |Entry {
+                     value: (k, vl),
+                     value_count: lcount,
+                 }| {
+                    right_values
+                        .get(k)
+                        .into_iter()
+                        .flatten()
+                        .map(move |(vr, &rcount)| {
+                            ((k.clone(), vl.clone(), vr.clone()), *lcount * rcount)
+                        })
+                },
+            )
+            .collect::<Vec<_>>();
+        for (t, count) in outputs {
+            f(t, count)
+        }
+        for e in self.left_changes_scratch.drain(..) {
+            let Entry {
+                value: (k, vl),
+                value_count,
+            } = e;
+            self.left_values.add((k, (vl, value_count)));
+        }
+
+        let left_values = &self.left_values;
+        let outputs = self
+            .right_changes_scratch
+            .par_iter()
+            .flat_map_iter(
+                |Entry {
+                     value: (k, vr),
+                     value_count: rcount,
+                 }| {
+                    left_values
+                        .get(k)
+                        .into_iter()
+                        .flatten()
+                        .map(move |(vl, &lcount)| {
+                            ((k.clone(), vl.clone(), vr.clone()), lcount * *rcount)
+                        })
+                },
+            )
+            .collect::<Vec<_>>();
+        for (t, count) in outputs {
+            f(t, count)
+        }
+        for e in self.right_changes_scratch.drain(..) {
+            let Entry {
+                value: (k, vr),
+                value_count,
+            } = e;
+            self.right_values.add((k, (vr, value_count)));
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use generic_map::rollover_map::RolloverMap;
+use rayon::prelude::*;
+
+use crate::{
+    context::CommitId, entry::Entry, generic_map::AddMap, op::Op, relation::RelationInner,
+    value_count::ValueCount,
+};
+
+use super::reduce::update_output;
+
+pub struct ParReduce<K, V, Y, G, C> {
+    sub_rel: RelationInner<(K, V), C>,
+    g: G,
+    aggregated_values: HashMap<K, RolloverMap<V, ValueCount, 2>>,
+    outputs: HashMap<K, Y>,
+    encountered_changes_scratch: Vec<Entry<(K, V)>>,
+    changed_keys_scratch: HashSet<K>,
+}
+
+impl<K, V, Y, G, C> ParReduce<K, V, Y, G, C> {
+    pub(crate) fn new(sub_rel: RelationInner<(K, V), C>, g: G) -> Self {
+        Self {
+            sub_rel,
+            g,
+            aggregated_values: HashMap::new(),
+            outputs: HashMap::new(),
+            encountered_changes_scratch: Vec::new(),
+            changed_keys_scratch: HashSet::new(),
+        }
+    }
+}
+
+impl<K, V, Y, G, C> Op<(K, Y)> for ParReduce<K, V, Y, G, C>
+where
+    K: Eq + Hash + Clone + Send + Sync,
+    V: Eq + Hash + Send + Sync,
+    Y: Eq + Clone + Send,
+    G: Fn(&K, &RolloverMap<V, ValueCount, 2>) -> Y + Sync,
+    C: Op<(K, V)>,
+{
+    fn type_name(&self) -> &'static str {
+        "par_reduce"
+    }
+    fn foreach<F: FnMut((K, Y), ValueCount)>(&mut self, current_id: CommitId, mut f: F) {
+        self.sub_rel
+            .dump_to_vec(current_id, &mut self.encountered_changes_scratch);
+        for e in self.encountered_changes_scratch.drain(..) {
+            let Entry {
+                value: (k, v),
+                value_count,
+            } = e;
+            self.changed_keys_scratch.insert(k.clone());
+            self.aggregated_values.add((k, (v, value_count)));
+        }
+        let aggregated_values = &self.aggregated_values;
+        let g = &self.g;
+        let new_ys = self
+            .changed_keys_scratch
+            .par_drain()
+            .map(|k| {
+                let new_y = aggregated_values.get(&k).map(|vals| g(&k, vals));
+                (k, new_y)
+            })
+            .collect::<Vec<_>>();
+        for (k, new_y) in new_ys {
+            update_output(&mut self.outputs, k, new_y, &mut f);
+        }
+    }
+}
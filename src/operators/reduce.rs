@@ -57,30 +57,40 @@ where
             self.aggregated_values.add((k, (v, value_count)));
         }
         for k in self.changed_keys_scratch.drain() {
-            match self.aggregated_values.get(&k) {
-                None => {
-                    if let Some(y) = self.outputs.remove(&k) {
-                        f((k, y), ValueCount(-1))
-                    }
-                }
-                Some(vals) => {
-                    let new_y = (self.g)(&k, vals);
-                    match self.outputs.entry(k.clone()) {
-                        hash_map::Entry::Vacant(vac) => {
-                            vac.insert(new_y.clone());
-                            f((k, new_y), ValueCount(1));
-                        }
-                        hash_map::Entry::Occupied(mut occ) => {
-                            let out = occ.get_mut();
-                            if new_y != *out {
-                                let old_y = mem::replace(out, new_y.clone());
-                                f((k.clone(), old_y), ValueCount(-1));
-                                f((k, new_y), ValueCount(1));
-                            }
-                        }
-                    }
-                }
+            let new_y = self
+                .aggregated_values
+                .get(&k)
+                .map(|vals| (self.g)(&k, vals));
+            update_output(&mut self.outputs, k, new_y, &mut f);
+        }
+    }
+}
+
+pub(crate) fn update_output<K, Y, F>(outputs: &mut HashMap<K, Y>, k: K, new_y: Option<Y>, f: &mut F)
+where
+    K: Eq + Hash + Clone,
+    Y: Eq + Clone,
+    F: FnMut((K, Y), ValueCount),
+{
+    match new_y {
+        None => {
+            if let Some(y) = outputs.remove(&k) {
+                f((k, y), ValueCount(-1))
             }
         }
+        Some(new_y) => match outputs.entry(k.clone()) {
+            hash_map::Entry::Vacant(vac) => {
+                vac.insert(new_y.clone());
+                f((k, new_y), ValueCount(1));
+            }
+            hash_map::Entry::Occupied(mut occ) => {
+                let out = occ.get_mut();
+                if new_y != *out {
+                    let old_y = mem::replace(out, new_y.clone());
+                    f((k.clone(), old_y), ValueCount(-1));
+                    f((k, new_y), ValueCount(1));
+                }
+            }
+        },
     }
 }
@@ -36,7 +36,8 @@ pub struct SavedOp<T, C> {
     receiver: Receiver<(T, ValueCount)>,
 }
 
-impl<T: Clone, C: Op<T>> Saved<T, C> {
+impl<T: Clone + Eq + Hash, C: Op<T>> Saved<T, C> {
+    #[track_caller]
     pub fn get(&self) -> Relation<T, SavedOp<T, C>> {
         let mut inner = self.0.borrow_mut();
         let receiver = inner.sender.subscribe();
@@ -52,23 +53,21 @@ impl<T: Clone, C: Op<T>> Saved<T, C> {
         .hidden()
     }
 
-    pub fn set_minus(&self, other: Relation<T, impl Op<T>>) -> Relation<T, impl Op<T>>
-    where
-        T: Clone + Eq + Hash,
-    {
+    #[track_caller]
+    pub fn set_minus(&self, other: Relation<T, impl Op<T>>) -> Relation<T, impl Op<T>> {
         self.get()
             .minus(self.get().intersection(other.distinct().hidden()))
     }
 }
 
 impl<K: Clone + Eq + Hash, V: Clone + Eq + Hash, C: Op<(K, V)>> Saved<(K, V), C> {
+    #[track_caller]
     pub fn antijoin<CR: Op<K>>(&self, other: Relation<K, CR>) -> Relation<(K, V), impl Op<(K, V)>> {
-        self.get()
-            .minus(self.get().semijoin(other.distinct().hidden()))
+        self.get().antijoin(other)
     }
 }
 
-impl<T: Clone, C: Op<T>> Op<T> for SavedOp<T, C> {
+impl<T: Clone + Eq + Hash, C: Op<T>> Op<T> for SavedOp<T, C> {
     fn type_name(&self) -> &'static str {
         "save"
     }
@@ -83,6 +82,7 @@ impl<T: Clone, C: Op<T>> Op<T> for SavedOp<T, C> {
         } = &mut *inner;
         if *last_id < current_id {
             sub_rel.send_to_broadcast(current_id, sender);
+            sender.consolidate();
             *last_id = current_id
         }
         while let Some((t, count)) = self.receiver.try_recv() {
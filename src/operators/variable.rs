@@ -0,0 +1,27 @@
+use std::hash::Hash;
+
+use crate::{
+    operators::{
+        input::{Input, InputOp},
+        save::{Saved, SavedOp},
+    },
+    relation::Relation,
+};
+
+pub struct Variable<T> {
+    pub(crate) saved: Saved<T, InputOp<T>>,
+}
+
+pub struct VariableFeed<T> {
+    pub(crate) input: Input<T>,
+}
+
+impl<T: Clone + Eq + Hash> Variable<T> {
+    pub fn get(&self) -> Relation<T, SavedOp<T, InputOp<T>>> {
+        self.saved.get()
+    }
+
+    pub fn into_saved(self) -> Saved<T, InputOp<T>> {
+        self.saved
+    }
+}
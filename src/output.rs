@@ -2,11 +2,13 @@ use std::{
     cell::{Cell, Ref, RefCell},
     collections::HashMap,
     hash::Hash,
+    mem,
     rc::Rc,
 };
 
 use crate::{
     context::CommitId,
+    generic_map::AddMap,
     op::{DynOp, Op},
     operators::save::SavedOp,
     relation::RelationInner,
@@ -16,11 +18,26 @@ use crate::{
 struct OutputInner<T, C> {
     relation: RelationInner<T, C>,
     values: HashMap<T, ValueCount>,
+    changes: Option<Changes<T>>,
+    changed_values_scratch: HashMap<T, ValueCount>,
+}
+
+struct Changes<T> {
+    values: HashMap<T, ValueCount>,
+    clone: fn(&T) -> T,
 }
 
 impl<T: Eq + Hash, C: Op<T>> OutputInner<T, C> {
     fn update(&mut self, commit_id: CommitId) {
-        self.relation.dump_to_map(commit_id, &mut self.values)
+        let Some(changes) = &mut self.changes else {
+            return self.relation.dump_to_map(commit_id, &mut self.values);
+        };
+        self.relation
+            .dump_to_map(commit_id, &mut self.changed_values_scratch);
+        for (value, count) in self.changed_values_scratch.drain() {
+            changes.values.add(((changes.clone)(&value), count));
+            self.values.add((value, count));
+        }
     }
 }
 
@@ -37,12 +54,14 @@ impl<T, C> Output<T, C> {
             inner: RefCell::new(OutputInner {
                 relation,
                 values: HashMap::new(),
+                changes: None,
+                changed_values_scratch: HashMap::new(),
             }),
             commit_id,
         }
     }
 
-    pub fn get(&self) -> Ref<HashMap<T, ValueCount>>
+    pub fn get(&self) -> Ref<'_, HashMap<T, ValueCount>>
     where
         T: Eq + Hash,
         C: Op<T>,
@@ -50,4 +69,23 @@ impl<T, C> Output<T, C> {
         self.inner.borrow_mut().update(self.commit_id.get());
         Ref::map(self.inner.borrow(), |inner| &inner.values)
     }
+
+    pub fn changes(&self) -> HashMap<T, ValueCount>
+    where
+        T: Eq + Hash + Clone,
+        C: Op<T>,
+    {
+        let mut inner = self.inner.borrow_mut();
+        inner.update(self.commit_id.get());
+        match &mut inner.changes {
+            Some(changes) => mem::take(&mut changes.values),
+            None => {
+                inner.changes = Some(Changes {
+                    values: HashMap::new(),
+                    clone: T::clone,
+                });
+                inner.values.clone()
+            }
+        }
+    }
 }
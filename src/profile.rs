@@ -0,0 +1,142 @@
+use std::{
+    cell::Cell,
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{arc_key::ArcKey, graph::representative, relation::data::RelationData};
+
+#[derive(Default)]
+pub(crate) struct RelationStats {
+    timed: AtomicBool,
+    visit_count: AtomicUsize,
+    elapsed_nanos: AtomicU64,
+    commit_start_visit_count: AtomicUsize,
+    commit_start_elapsed_nanos: AtomicU64,
+}
+
+impl RelationStats {
+    pub(crate) fn is_timed(&self) -> bool {
+        self.timed.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_timed(&self, timed: bool) {
+        self.timed.store(timed, Ordering::Relaxed)
+    }
+
+    pub(crate) fn visit(&self) {
+        self.visit_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn start_commit(&self) {
+        self.commit_start_visit_count
+            .store(self.visit_count.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.commit_start_elapsed_nanos.store(
+            self.elapsed_nanos.load(Ordering::Relaxed),
+            Ordering::Relaxed,
+        );
+    }
+
+    pub(crate) fn reset(&self) {
+        self.visit_count.store(0, Ordering::Relaxed);
+        self.elapsed_nanos.store(0, Ordering::Relaxed);
+        self.commit_start_visit_count.store(0, Ordering::Relaxed);
+        self.commit_start_elapsed_nanos.store(0, Ordering::Relaxed);
+    }
+}
+
+thread_local! {
+    static CHILDREN_ELAPSED: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+pub(crate) struct Timer {
+    start: Instant,
+    outer_children_elapsed: Duration,
+}
+
+impl Timer {
+    pub(crate) fn start() -> Self {
+        Self {
+            outer_children_elapsed: CHILDREN_ELAPSED.with(|c| c.replace(Duration::ZERO)),
+            start: Instant::now(),
+        }
+    }
+
+    pub(crate) fn stop(self, stats: &RelationStats) {
+        let elapsed = self.start.elapsed();
+        let children_elapsed =
+            CHILDREN_ELAPSED.with(|c| c.replace(self.outer_children_elapsed + elapsed));
+        let own_elapsed = elapsed.saturating_sub(children_elapsed);
+        stats
+            .elapsed_nanos
+            .fetch_add(own_elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+/// `elapsed` is the time spent in the relation's `foreach` minus the time spent in
+/// its children's. Work done by the consumer for each tuple happens inside that call,
+/// so it is counted against the relation producing the tuples.
+///
+/// The `last_commit_*` fields cover everything since the last commit started,
+/// including any `push_frame` or `rollback_to` made after it.
+#[derive(Clone, Debug)]
+pub struct RelationProfile {
+    pub name: Option<String>,
+    pub type_name: &'static str,
+    pub visits: usize,
+    pub elapsed: Duration,
+    pub last_commit_visits: usize,
+    pub last_commit_elapsed: Duration,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    pub relations: Vec<RelationProfile>,
+}
+
+impl RelationProfile {
+    fn new(data: &RelationData) -> Self {
+        Self {
+            name: data.name.clone(),
+            type_name: data.type_name,
+            visits: 0,
+            elapsed: Duration::ZERO,
+            last_commit_visits: 0,
+            last_commit_elapsed: Duration::ZERO,
+        }
+    }
+}
+
+impl Profile {
+    #[allow(clippy::mutable_key_type)]
+    pub(crate) fn new(relational_graph: &HashSet<ArcKey<RelationData>>) -> Self {
+        let mut relations = HashMap::<ArcKey<RelationData>, RelationProfile>::new();
+        for data in relational_graph {
+            let shown = representative(&data.0);
+            let profile = relations
+                .entry(ArcKey(shown.clone()))
+                .or_insert_with(|| RelationProfile::new(shown));
+            let stats = &data.stats;
+            let visits = stats.visit_count.load(Ordering::Relaxed);
+            let elapsed_nanos = stats.elapsed_nanos.load(Ordering::Relaxed);
+            profile.visits += visits;
+            profile.elapsed += Duration::from_nanos(elapsed_nanos);
+            profile.last_commit_visits +=
+                visits - stats.commit_start_visit_count.load(Ordering::Relaxed);
+            profile.last_commit_elapsed += Duration::from_nanos(
+                elapsed_nanos - stats.commit_start_elapsed_nanos.load(Ordering::Relaxed),
+            );
+        }
+        let mut relations = relations.into_values().collect::<Vec<_>>();
+        relations.sort_by_key(|relation| Reverse((relation.elapsed, relation.visits)));
+        Self { relations }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RelationProfile> {
+        self.relations
+            .iter()
+            .find(|relation| relation.name.as_deref() == Some(name))
+    }
+}
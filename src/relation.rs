@@ -1,15 +1,7 @@
 #![allow(clippy::type_complexity)]
 
 use std::{
-    collections::HashMap,
-    convert::identity,
-    hash::Hash,
-    iter,
-    marker::PhantomData,
-    sync::{
-        atomic::{self, AtomicUsize},
-        Arc,
-    },
+    collections::HashMap, convert::identity, hash::Hash, iter, marker::PhantomData, sync::Arc,
 };
 
 use generic_map::{
@@ -26,21 +18,28 @@ use crate::{
     nullable::Nullable,
     op::{DynOp, Op},
     operators::{
+        antijoin::AntiJoin,
+        arrange::Arranged,
         concat::Concat,
         consolidate::Consolidate,
         flat_map::FlatMap,
         join::InnerJoin,
         negate::Negate,
+        outer_join::OuterJoin,
         reduce::Reduce,
         save::Saved,
         split::{Split, SplitOp},
     },
+    profile::{RelationStats, Timer},
     value_count::ValueCount,
 };
 
+#[cfg(feature = "parallel")]
+use crate::operators::{par_join::ParJoin, par_reduce::ParReduce};
+
 use self::{args::RelationArgs, data::RelationData};
 
-mod args;
+pub(crate) mod args;
 
 pub(crate) mod data;
 
@@ -51,24 +50,40 @@ pub struct Relation<T, C = Box<dyn DynOp<T>>> {
 }
 
 pub struct RelationInfo {
-    visit_count: Arc<AtomicUsize>,
+    stats: Arc<RelationStats>,
 }
 
 impl RelationInfo {
-    pub(crate) fn visit(&mut self) {
-        self.visit_count.fetch_add(1, atomic::Ordering::Relaxed);
+    pub fn visit(&mut self) {
+        self.stats.visit()
+    }
+
+    fn start_timer(&self) -> Option<Timer> {
+        self.stats.is_timed().then(Timer::start)
+    }
+
+    fn stop_timer(&self, timer: Option<Timer>) {
+        if let Some(timer) = timer {
+            timer.stop(&self.stats)
+        }
     }
 }
 
-pub(crate) struct RelationInner<T, C> {
+pub struct RelationInner<T, C> {
     phantom: PhantomData<T>,
     info: RelationInfo,
     operator: C,
 }
 
 impl<T, C: Op<T>> RelationInner<T, C> {
-    pub(crate) fn foreach(&mut self, current_id: CommitId, mut f: impl FnMut(T, ValueCount)) {
-        self.operator.foreach(current_id, |x, v| f(x, v))
+    pub fn foreach(&mut self, current_id: CommitId, mut f: impl FnMut(T, ValueCount)) {
+        let timer = self.info.start_timer();
+        let info = &mut self.info;
+        self.operator.foreach(current_id, |x, v| {
+            info.visit();
+            f(x, v)
+        });
+        self.info.stop_timer(timer)
     }
 
     pub(crate) fn send_to_broadcast(
@@ -78,19 +93,25 @@ impl<T, C: Op<T>> RelationInner<T, C> {
     ) where
         T: Clone,
     {
+        let timer = self.info.start_timer();
         self.operator
-            .send_to_broadcast(current_id, &mut self.info, broadcast)
+            .send_to_broadcast(current_id, &mut self.info, broadcast);
+        self.info.stop_timer(timer)
     }
 
     pub(crate) fn dump_to_vec(&mut self, current_id: CommitId, vec: &mut Vec<Entry<T>>) {
+        let timer = self.info.start_timer();
         self.operator.dump_to_vec(current_id, &mut self.info, vec);
+        self.info.stop_timer(timer)
     }
 
-    pub(crate) fn dump_to_map(&mut self, current_id: CommitId, map: &mut HashMap<T, ValueCount>)
+    pub fn dump_to_map(&mut self, current_id: CommitId, map: &mut HashMap<T, ValueCount>)
     where
         T: Eq + Hash,
     {
-        self.operator.dump_to_map(current_id, &mut self.info, map)
+        let timer = self.info.start_timer();
+        self.operator.dump_to_map(current_id, &mut self.info, map);
+        self.info.stop_timer(timer)
     }
 }
 
@@ -101,7 +122,7 @@ impl<T, C> Relation<T, C> {
             inner: RelationInner {
                 phantom: PhantomData,
                 info: RelationInfo {
-                    visit_count: data.visit_count.clone(),
+                    stats: data.stats.clone(),
                 },
                 operator,
             },
@@ -129,7 +150,11 @@ impl<T, C> Relation<T, C> {
 }
 
 impl<T, C: Op<T>> Relation<T, C> {
-    pub(crate) fn from_op<Subrels: RelationArgs>(
+    /// Wraps `subrels` (a relation, or a pair of `RelationArgs`) into a new relation whose
+    /// operator is built from their `RelationInner`s. The new node appears in the dataflow
+    /// graph and profile under `Op::type_name`, and can be renamed with `named`.
+    #[track_caller]
+    pub fn from_op<Subrels: RelationArgs>(
         subrels: Subrels,
         operator: impl FnOnce(Subrels::Inner) -> C,
     ) -> Self {
@@ -149,6 +174,7 @@ impl<T, C: Op<T>> Relation<T, C> {
         Relation::new(self.context_id, self.data, Box::new(self.inner.operator))
     }
 
+    #[track_caller]
     pub fn flat_map<U, G: Fn(T) -> I, I>(self, g: G) -> Relation<U, FlatMap<T, G, C>>
     where
         I: IntoIterator<Item = U>,
@@ -156,6 +182,7 @@ impl<T, C: Op<T>> Relation<T, C> {
         Relation::from_op(self, |r| FlatMap::new(r, g))
     }
 
+    #[track_caller]
     pub fn consolidate(self) -> Relation<T, Consolidate<T, C>>
     where
         T: Clone + Eq + Hash,
@@ -163,6 +190,7 @@ impl<T, C: Op<T>> Relation<T, C> {
         Relation::from_op(self, Consolidate::new)
     }
 
+    #[track_caller]
     pub fn consolidate_h(self) -> Relation<T, Consolidate<T, C>>
     where
         T: Clone + Eq + Hash,
@@ -170,6 +198,7 @@ impl<T, C: Op<T>> Relation<T, C> {
         self.consolidate().hidden()
     }
 
+    #[track_caller]
     pub fn concat<CR>(self, other: Relation<T, CR>) -> Relation<T, Consolidate<T, Concat<T, C, CR>>>
     where
         CR: Op<T>,
@@ -178,6 +207,7 @@ impl<T, C: Op<T>> Relation<T, C> {
         Relation::from_op((self, other), Concat::new).consolidate_h()
     }
 
+    #[track_caller]
     pub fn negate(self) -> Relation<T, Negate<T, C>> {
         Relation::from_op(self, Negate::new)
     }
@@ -186,6 +216,7 @@ impl<T, C: Op<T>> Relation<T, C> {
         Saved::new(self)
     }
 
+    #[track_caller]
     pub fn minus<CR>(
         self,
         other: Relation<T, CR>,
@@ -197,6 +228,7 @@ impl<T, C: Op<T>> Relation<T, C> {
         self.concat(other.negate().hidden()).type_named("minus")
     }
 
+    #[track_caller]
     pub fn distinct(self) -> Relation<T, impl Op<T>>
     where
         T: Eq + Hash + Clone,
@@ -207,6 +239,7 @@ impl<T, C: Op<T>> Relation<T, C> {
             .type_named("distinct")
     }
 
+    #[track_caller]
     pub fn flatten<U>(self) -> Relation<U, impl Op<U>>
     where
         T: IntoIterator<Item = U>,
@@ -214,10 +247,12 @@ impl<T, C: Op<T>> Relation<T, C> {
         self.flat_map(identity).type_named("flatten")
     }
 
+    #[track_caller]
     pub fn map<U>(self, f: impl Fn(T) -> U) -> Relation<U, impl Op<U>> {
         self.flat_map(move |x| iter::once(f(x))).type_named("map")
     }
 
+    #[track_caller]
     pub fn filter(self, f: impl Fn(&T) -> bool) -> Relation<T, impl Op<T>>
     where
         C: Op<T>,
@@ -226,6 +261,7 @@ impl<T, C: Op<T>> Relation<T, C> {
             .type_named("filter")
     }
 
+    #[track_caller]
     pub fn map_h<U>(self, f: impl Fn(T) -> U) -> Relation<U, impl Op<U>>
     where
         C: Op<T>,
@@ -242,6 +278,7 @@ impl<T, C: Op<T>> Relation<T, C> {
 }
 
 impl<T: Eq + Hash + Clone, C: Op<T>> Relation<T, C> {
+    #[track_caller]
     pub fn intersection(self, other: Relation<T, impl Op<T>>) -> Relation<T, impl Op<T>> {
         self.map_h(|t| (t, ()))
             .join(other.map_h(|t| (t, ())))
@@ -249,6 +286,7 @@ impl<T: Eq + Hash + Clone, C: Op<T>> Relation<T, C> {
             .type_named("intersection")
     }
 
+    #[track_caller]
     pub fn counts(self) -> Relation<(T, isize), impl Op<(T, isize)>> {
         self.map_h(|t| (t, ()))
             .reduce_gen(|_, vals: &RolloverMap<(), ValueCount>| {
@@ -258,6 +296,7 @@ impl<T: Eq + Hash + Clone, C: Op<T>> Relation<T, C> {
             .type_named("counts")
     }
 
+    #[track_caller]
     pub fn global_max(self) -> Relation<T, impl Op<T>>
     where
         T: Clone + Ord,
@@ -268,6 +307,7 @@ impl<T: Eq + Hash + Clone, C: Op<T>> Relation<T, C> {
             .type_named("global_max")
     }
 
+    #[track_caller]
     pub fn global_min(self) -> Relation<T, impl Op<T>>
     where
         T: Clone + Ord,
@@ -285,6 +325,7 @@ where
     V: Eq + Hash + Clone,
     C: Op<(K, V)>,
 {
+    #[track_caller]
     pub fn join<VR, CR>(
         self,
         other: Relation<(K, VR), CR>,
@@ -296,6 +337,36 @@ where
         Relation::from_op((self, other), InnerJoin::new).consolidate_h()
     }
 
+    #[track_caller]
+    pub fn left_join<VR, CR>(
+        self,
+        other: Relation<(K, VR), CR>,
+    ) -> Relation<(K, V, Option<VR>), impl Op<(K, V, Option<VR>)>>
+    where
+        VR: Eq + Hash + Clone,
+        CR: Op<(K, VR)>,
+    {
+        Relation::from_op((self, other), |rels| OuterJoin::new(rels, false))
+            .consolidate_h()
+            .map_h(|(k, vl, vr)| (k, vl.unwrap(), vr))
+    }
+
+    #[track_caller]
+    pub fn outer_join<VR, CR>(
+        self,
+        other: Relation<(K, VR), CR>,
+    ) -> Relation<
+        (K, Option<V>, Option<VR>),
+        Consolidate<(K, Option<V>, Option<VR>), OuterJoin<K, V, C, VR, CR>>,
+    >
+    where
+        VR: Eq + Hash + Clone,
+        CR: Op<(K, VR)>,
+    {
+        Relation::from_op((self, other), |rels| OuterJoin::new(rels, true)).consolidate_h()
+    }
+
+    #[track_caller]
     pub fn reduce<Y, G: Fn(&K, &RolloverMap<V, ValueCount, 2>) -> Y>(
         self,
         g: G,
@@ -306,6 +377,7 @@ where
         self.reduce_gen(g)
     }
 
+    #[track_caller]
     fn reduce_gen<Y, M, G: Fn(&K, &M) -> Y>(
         self,
         g: G,
@@ -317,12 +389,62 @@ where
         Relation::from_op(self, |r| Reduce::new(r, g)).consolidate_h()
     }
 
+    /// Like `join`, but probes the changed keys on the rayon pool. This is the only
+    /// parallelism the `parallel` feature provides: operators share state through
+    /// `Rc<RefCell<..>>` channels, so independent subgraphs are still pulled one after
+    /// another on the committing thread.
+    #[cfg(feature = "parallel")]
+    #[track_caller]
+    pub fn par_join<VR, CR>(
+        self,
+        other: Relation<(K, VR), CR>,
+    ) -> Relation<(K, V, VR), Consolidate<(K, V, VR), ParJoin<K, V, C, VR, CR>>>
+    where
+        K: Send + Sync,
+        V: Send + Sync,
+        VR: Eq + Hash + Clone + Send + Sync,
+        CR: Op<(K, VR)>,
+    {
+        Relation::from_op((self, other), ParJoin::new).consolidate_h()
+    }
+
+    /// Like `reduce`, but evaluates `g` for the changed keys on the rayon pool. See
+    /// `par_join` for what the `parallel` feature does not cover.
+    #[cfg(feature = "parallel")]
+    #[track_caller]
+    pub fn par_reduce<Y, G>(
+        self,
+        g: G,
+    ) -> Relation<(K, Y), Consolidate<(K, Y), ParReduce<K, V, Y, G, C>>>
+    where
+        K: Send + Sync,
+        V: Send + Sync,
+        Y: Eq + Hash + Clone + Send,
+        G: Fn(&K, &RolloverMap<V, ValueCount, 2>) -> Y + Sync,
+    {
+        Relation::from_op(self, |r| ParReduce::new(r, g)).consolidate_h()
+    }
+
+    #[track_caller]
     pub fn semijoin(self, other: Relation<K, impl Op<K>>) -> Relation<(K, V), impl Op<(K, V)>> {
         self.join(other.map_h(|t| (t, ())))
             .map_h(|(k, v, ())| (k, v))
             .type_named("semijoin")
     }
 
+    #[track_caller]
+    pub fn antijoin<CR: Op<K>>(
+        self,
+        other: Relation<K, CR>,
+    ) -> Relation<(K, V), Consolidate<(K, V), AntiJoin<K, V, C, CR>>> {
+        Relation::from_op((self, other), AntiJoin::new).consolidate_h()
+    }
+
+    pub fn arrange_by_key(self) -> Arranged<K, V, C> {
+        Arranged::new(self)
+    }
+
+    #[track_caller]
     pub fn join_values<VR: Eq + Hash + Clone>(
         self,
         other: Relation<(K, VR), impl Op<(K, VR)>>,
@@ -330,6 +452,7 @@ where
         self.join(other).map_h(|(_k, vl, vr)| (vl, vr))
     }
 
+    #[track_caller]
     pub fn maxes(self) -> Relation<(K, V), impl Op<(K, V)>>
     where
         V: Clone + Ord,
@@ -340,6 +463,7 @@ where
         .type_named("maxes")
     }
 
+    #[track_caller]
     pub fn mins(self) -> Relation<(K, V), impl Op<(K, V)>>
     where
         V: Clone + Ord,
@@ -352,6 +476,7 @@ where
 }
 
 impl<L, R, C: Op<(L, R)>> Relation<(L, R), C> {
+    #[track_caller]
     pub fn split(
         self,
     ) -> (
@@ -376,18 +501,21 @@ impl<L, R, C: Op<(L, R)>> Relation<(L, R), C> {
             .hidden(),
         )
     }
+    #[track_caller]
     pub fn fsts(self) -> Relation<L, impl Op<L>>
     where
         C: Op<(L, R)>,
     {
         self.map_h(|(l, _r)| l)
     }
+    #[track_caller]
     pub fn snds(self) -> Relation<R, impl Op<R>>
     where
         C: Op<(L, R)>,
     {
         self.map_h(|(_l, r)| r)
     }
+    #[track_caller]
     pub fn swaps(self) -> Relation<(R, L), impl Op<(R, L)>>
     where
         C: Op<(L, R)>,
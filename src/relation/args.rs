@@ -1,3 +1,7 @@
+// `Sealed` cannot be named outside the crate, so the crate-private types in its
+// methods never reach users even though the lint considers them reachable.
+#![allow(private_interfaces)]
+
 use std::sync::Arc;
 
 use generic_map::rollover_map::RolloverMap;
@@ -6,14 +10,27 @@ use crate::{context::ContextId, generic_map::AddMap, relation::Relation, value_c
 
 use super::{data::RelationData, RelationInner};
 
-pub(crate) trait RelationArgs {
-    type Inner;
+/// The inputs accepted by `Relation::from_op`: a relation, or a pair of inputs. This
+/// trait is sealed.
+pub trait RelationArgs: sealed::Sealed {}
+
+impl<A: sealed::Sealed> RelationArgs for A {}
+
+mod sealed {
+    use super::*;
 
-    fn add_context_ids(&self, s: &mut RolloverMap<ContextId, ValueCount>);
-    fn push_datas(self, _v: &mut Vec<Arc<RelationData>>) -> Self::Inner;
+    #[doc(hidden)]
+    pub trait Sealed {
+        type Inner;
+
+        fn add_context_ids(&self, s: &mut RolloverMap<ContextId, ValueCount>);
+        fn push_datas(self, _v: &mut Vec<Arc<RelationData>>) -> Self::Inner;
+    }
 }
 
-impl RelationArgs for ContextId {
+pub(crate) use self::sealed::Sealed;
+
+impl Sealed for ContextId {
     type Inner = ();
 
     fn add_context_ids(&self, s: &mut RolloverMap<ContextId, ValueCount>) {
@@ -22,7 +39,7 @@ impl RelationArgs for ContextId {
     fn push_datas(self, _v: &mut Vec<Arc<RelationData>>) -> Self::Inner {}
 }
 
-impl<T, C> RelationArgs for Relation<T, C> {
+impl<T, C> Sealed for Relation<T, C> {
     type Inner = RelationInner<T, C>;
 
     fn add_context_ids(&self, s: &mut RolloverMap<ContextId, ValueCount>) {
@@ -34,10 +51,10 @@ impl<T, C> RelationArgs for Relation<T, C> {
     }
 }
 
-impl<A, B> RelationArgs for (A, B)
+impl<A, B> Sealed for (A, B)
 where
-    A: RelationArgs,
-    B: RelationArgs,
+    A: Sealed,
+    B: Sealed,
 {
     type Inner = (A::Inner, B::Inner);
 
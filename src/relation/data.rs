@@ -1,20 +1,29 @@
-use std::sync::{atomic::AtomicUsize, Arc};
+use std::{panic::Location, sync::Arc};
+
+use crate::{context::InputId, profile::RelationStats};
 
 pub(crate) struct RelationData {
     pub(crate) name: Option<String>,
     pub(crate) type_name: &'static str,
+    pub(crate) op_type_name: &'static str,
+    pub(crate) location: &'static Location<'static>,
     pub(crate) hidden: bool,
     pub(crate) children: Vec<Arc<RelationData>>,
-    pub(crate) visit_count: Arc<AtomicUsize>,
+    pub(crate) stats: Arc<RelationStats>,
+    pub(crate) input_id: Option<InputId>,
 }
 impl RelationData {
+    #[track_caller]
     pub(crate) fn new(type_name: &'static str, children: Vec<Arc<RelationData>>) -> Self {
         Self {
             name: None,
             type_name,
+            op_type_name: type_name,
+            location: Location::caller(),
             hidden: false,
             children,
-            visit_count: Arc::new(AtomicUsize::new(0)),
+            stats: Arc::default(),
+            input_id: None,
         }
     }
 
@@ -0,0 +1,223 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt,
+    hash::Hash,
+    vec,
+};
+
+use crate::{
+    context::{CommitError, ExecutionContext, FrameId, InterruptHandle, InterruptId},
+    op::{DynOp, Op},
+    operators::input::Input,
+    output::Output,
+    value_count::ValueCount,
+};
+
+pub trait ChoiceOrder<T> {
+    fn order(&mut self, candidates: &HashMap<T, ValueCount>) -> Vec<T>;
+}
+
+impl<T, F: FnMut(&HashMap<T, ValueCount>) -> Vec<T>> ChoiceOrder<T> for F {
+    fn order(&mut self, candidates: &HashMap<T, ValueCount>) -> Vec<T> {
+        self(candidates)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Unordered;
+
+impl<T: Clone> ChoiceOrder<T> for Unordered {
+    fn order(&mut self, candidates: &HashMap<T, ValueCount>) -> Vec<T> {
+        candidates.keys().cloned().collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ascending;
+
+impl<T: Ord + Clone> ChoiceOrder<T> for Ascending {
+    fn order(&mut self, candidates: &HashMap<T, ValueCount>) -> Vec<T> {
+        let mut result = candidates.keys().cloned().collect::<Vec<_>>();
+        result.sort();
+        result
+    }
+}
+
+// Branches only on the values of the key with the fewest candidates, so each
+// solution is reached exactly once when every key needs exactly one value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FewestOptions;
+
+impl<K: Ord + Clone, V: Ord + Clone> ChoiceOrder<(K, V)> for FewestOptions {
+    fn order(&mut self, candidates: &HashMap<(K, V), ValueCount>) -> Vec<(K, V)> {
+        let mut by_key = BTreeMap::<&K, Vec<&V>>::new();
+        for (k, v) in candidates.keys() {
+            by_key.entry(k).or_default().push(v);
+        }
+        let Some((k, mut vs)) = by_key.into_iter().min_by_key(|(_, vs)| vs.len()) else {
+            return Vec::new();
+        };
+        vs.sort();
+        vs.into_iter().map(|v| (k.clone(), v.clone())).collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchError {
+    ForeignInput,
+    InputDropped,
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::ForeignInput => write!(f, "choice input belongs to another context"),
+            SearchError::InputDropped => write!(f, "choice input no longer feeds the context"),
+        }
+    }
+}
+
+impl Error for SearchError {}
+
+enum State {
+    Start,
+    Running(FrameId),
+    Done,
+}
+
+struct Level<T> {
+    frame: FrameId,
+    alternatives: vec::IntoIter<T>,
+}
+
+pub struct Search<'s, 'a, T, S, C = Box<dyn DynOp<T>>> {
+    context: &'s mut ExecutionContext<'a>,
+    candidates: &'s Output<T, C>,
+    choose: Input<T>,
+    conflict: InterruptId,
+    solved: &'s InterruptHandle<S>,
+    order: Box<dyn ChoiceOrder<T> + 's>,
+    state: State,
+    levels: Vec<Level<T>>,
+}
+
+impl<'s, 'a, T, S, C> Search<'s, 'a, T, S, C>
+where
+    T: Eq + Hash + Clone + 's,
+    S: Eq + Hash + Clone,
+    C: Op<T>,
+{
+    pub fn new(
+        context: &'s mut ExecutionContext<'a>,
+        candidates: &'s Output<T, C>,
+        choose: Input<T>,
+        conflict: InterruptId,
+        solved: &'s InterruptHandle<S>,
+    ) -> Result<Self, SearchError> {
+        if !context.owns(&choose) {
+            return Err(SearchError::ForeignInput);
+        }
+        if !choose.is_connected() {
+            return Err(SearchError::InputDropped);
+        }
+        Ok(Search {
+            context,
+            candidates,
+            choose,
+            conflict,
+            solved,
+            order: Box::new(Unordered),
+            state: State::Start,
+            levels: Vec::new(),
+        })
+    }
+
+    pub fn order(mut self, order: impl ChoiceOrder<T> + 's) -> Self {
+        self.order = Box::new(order);
+        self
+    }
+
+    fn backtrack(&mut self) -> Option<T> {
+        while let Some(level) = self.levels.last_mut() {
+            self.context.rollback_to(level.frame);
+            if let Some(choice) = level.alternatives.next() {
+                level.frame = self.context.push_frame();
+                return Some(choice);
+            }
+            self.levels.pop();
+        }
+        self.finish();
+        None
+    }
+
+    fn finish(&mut self) {
+        if let State::Running(root) = self.state {
+            self.levels.clear();
+            self.context.rollback_to(root);
+        }
+        self.state = State::Done;
+    }
+}
+
+impl<'s, T, S, C> Iterator for Search<'s, '_, T, S, C>
+where
+    T: Eq + Hash + Clone + 's,
+    S: Eq + Hash + Clone,
+    C: Op<T>,
+{
+    type Item = Result<HashMap<S, ValueCount>, CommitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut choice = match self.state {
+            State::Start => {
+                self.state = State::Running(self.context.push_frame());
+                None
+            }
+            State::Running(_) => Some(self.backtrack()?),
+            State::Done => return None,
+        };
+        loop {
+            if let Some(choice) = choice.take() {
+                // `new` checked the input, but a pipe whose relation was dropped is
+                // only removed the next time the context runs it.
+                if self.choose.send(choice).is_err() {
+                    self.finish();
+                    return None;
+                }
+            }
+            match self.context.commit() {
+                Err(CommitError::Interrupted(id)) if id == self.conflict => {}
+                Err(CommitError::Interrupted(id)) if id == self.solved.id() => {
+                    return Some(Ok(self.solved.get().clone()))
+                }
+                Err(err) => {
+                    self.finish();
+                    return Some(Err(err));
+                }
+                Ok(()) => {
+                    let candidates = self.order.order(&self.candidates.get());
+                    let mut alternatives = candidates.into_iter();
+                    if let Some(first) = alternatives.next() {
+                        let frame = self.context.push_frame();
+                        self.levels.push(Level {
+                            frame,
+                            alternatives,
+                        });
+                        choice = Some(first);
+                        continue;
+                    }
+                }
+            }
+            choice = Some(self.backtrack()?);
+        }
+    }
+}
+
+impl<T, S, C> Drop for Search<'_, '_, T, S, C> {
+    fn drop(&mut self) {
+        if let State::Running(root) = self.state {
+            self.context.rollback_to(root);
+        }
+    }
+}
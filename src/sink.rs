@@ -0,0 +1,35 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::{context::InterruptId, value_count::ValueCount};
+
+pub trait Sink<T> {
+    fn receive(&mut self, changes: &HashMap<T, ValueCount>) -> Result<(), SinkError>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SinkError {
+    Interrupt(InterruptId),
+    Failed(String),
+}
+
+pub(crate) struct Callback<F>(pub(crate) F);
+
+impl<T, F: FnMut(&HashMap<T, ValueCount>)> Sink<T> for Callback<F> {
+    fn receive(&mut self, changes: &HashMap<T, ValueCount>) -> Result<(), SinkError> {
+        (self.0)(changes);
+        Ok(())
+    }
+}
+
+impl fmt::Display for SinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SinkError::Interrupt(interrupt_id) => {
+                write!(f, "sink requested interrupt {}", interrupt_id)
+            }
+            SinkError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for SinkError {}
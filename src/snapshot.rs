@@ -0,0 +1,86 @@
+use std::{error::Error, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::graph::DataflowGraph;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub(crate) commit_id: usize,
+    pub(crate) nodes: Vec<(Option<String>, String)>,
+    pub(crate) edges: Vec<(usize, usize)>,
+    pub(crate) inputs: Vec<(usize, serde_json::Value)>,
+}
+
+impl Snapshot {
+    pub(crate) fn matches(&self, graph: &DataflowGraph) -> bool {
+        self.edges == graph.edges
+            && self.nodes.len() == graph.nodes.len()
+            && self
+                .nodes
+                .iter()
+                .zip(graph.nodes.iter())
+                .all(|((name, type_name), node)| {
+                    *name == node.name && type_name.as_str() == node.type_name
+                })
+    }
+}
+
+pub(crate) fn graph_nodes(graph: &DataflowGraph) -> Vec<(Option<String>, String)> {
+    graph
+        .nodes
+        .iter()
+        .map(|node| (node.name.clone(), node.type_name.to_string()))
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    InFrame,
+    Interrupted,
+    Suspended,
+    NotPersisted,
+    AlreadyStarted,
+    GraphMismatch,
+    Serde(serde_json::Error),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::InFrame => write!(f, "cannot snapshot inside a frame"),
+            SnapshotError::Interrupted => {
+                write!(f, "cannot snapshot after an interrupted commit")
+            }
+            SnapshotError::Suspended => write!(f, "cannot snapshot with suspended sinks"),
+            SnapshotError::NotPersisted => {
+                write!(
+                    f,
+                    "cannot snapshot an input that was not created as persisted"
+                )
+            }
+            SnapshotError::AlreadyStarted => {
+                write!(f, "can only restore into a context that has not committed")
+            }
+            SnapshotError::GraphMismatch => {
+                write!(f, "snapshot was taken from a different dataflow graph")
+            }
+            SnapshotError::Serde(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnapshotError::Serde(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(err: serde_json::Error) -> Self {
+        SnapshotError::Serde(err)
+    }
+}
@@ -7,6 +7,7 @@ use redis::ToRedisArgs;
 use crate::nullable::Nullable;
 
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ValueCount(pub isize);
 impl ValueCount {
     pub fn min_magnitude(&self, other: Self) -> Self {
@@ -1,15 +1,14 @@
 use crate::ValueCount;
 
 pub(crate) enum Who {
-    User,
+    User(ValueCount),
     Feedback(ValueCount),
 }
 
 impl Who {
     pub(crate) fn value_count(&self) -> ValueCount {
         match self {
-            Who::User => ValueCount(1),
-            Who::Feedback(count) => *count,
+            Who::User(count) | Who::Feedback(count) => *count,
         }
     }
 }
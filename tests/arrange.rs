@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use standing_relations_2::{CreationContext, ValueCount};
+
+#[test]
+fn test_arranged_matches_unarranged() {
+    let mut context = CreationContext::new();
+    let (mut left_input, left) = context.input::<(char, usize)>();
+    let (mut right_input, right) = context.input::<(char, &str)>();
+    let (mut keys_input, keys) = context.input::<char>();
+    let left = left.save();
+    let right = right.save();
+    let keys = keys.save();
+    let arranged = left.get().arrange_by_key();
+
+    let joined = context.output(arranged.join(right.get()));
+    let expected_joined = context.output(left.get().join(right.get()));
+    let semijoined = context.output(arranged.semijoin(keys.get()));
+    let expected_semijoined = context.output(left.get().semijoin(keys.get()));
+    let antijoined = context.output(arranged.antijoin(keys.get()));
+    let expected_antijoined = context.output(left.get().antijoin(keys.get()));
+    let reduced =
+        context.output(arranged.reduce(|_, vals| vals.into_iter().map(|(v, _)| v).sum::<usize>()));
+    let expected_reduced = context.output(
+        left.get()
+            .reduce(|_, vals| vals.into_iter().map(|(v, _)| v).sum::<usize>()),
+    );
+    let self_joined = context.output(arranged.join(arranged.get()));
+    let mut context = context.begin();
+
+    left_input.send(('a', 1)).unwrap();
+    left_input.send(('a', 2)).unwrap();
+    left_input.send(('b', 3)).unwrap();
+    right_input.send(('a', "x")).unwrap();
+    keys_input.send('b').unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *joined.get(),
+        HashMap::from([
+            (('a', 1, "x"), ValueCount(1)),
+            (('a', 2, "x"), ValueCount(1)),
+        ])
+    );
+    assert_eq!(
+        *semijoined.get(),
+        HashMap::from([(('b', 3), ValueCount(1))])
+    );
+    assert_eq!(
+        *reduced.get(),
+        HashMap::from([(('a', 3), ValueCount(1)), (('b', 3), ValueCount(1))])
+    );
+    assert_eq!(self_joined.get().len(), 5);
+
+    right_input.send(('b', "y")).unwrap();
+    left_input.remove(('a', 1)).unwrap();
+    keys_input.send('a').unwrap();
+    keys_input.remove('b').unwrap();
+    context.commit().unwrap();
+    left_input.send(('b', 4)).unwrap();
+    right_input.remove(('a', "x")).unwrap();
+    context.commit().unwrap();
+
+    assert_eq!(*joined.get(), *expected_joined.get());
+    assert_eq!(*semijoined.get(), *expected_semijoined.get());
+    assert_eq!(*antijoined.get(), *expected_antijoined.get());
+    assert_eq!(*reduced.get(), *expected_reduced.get());
+    assert_eq!(
+        *joined.get(),
+        HashMap::from([
+            (('b', 3, "y"), ValueCount(1)),
+            (('b', 4, "y"), ValueCount(1)),
+        ])
+    );
+    assert_eq!(
+        *antijoined.get(),
+        HashMap::from([(('b', 3), ValueCount(1)), (('b', 4), ValueCount(1))])
+    );
+    assert_eq!(self_joined.get().len(), 5);
+}
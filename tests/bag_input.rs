@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use standing_relations_2::{CreationContext, ValueCount};
+
+#[test]
+fn test_bag_input() {
+    let mut context = CreationContext::new();
+    let (mut input, rel) = context.bag_input::<&str>();
+    let counts = context.output(rel.map(|_| ()).counts());
+    let (mut other_input, other_rel) = context.bag_input::<&str>();
+    let other = context.output(other_rel);
+    let mut context = context.begin();
+
+    input.send("apple").unwrap();
+    input.send("apple").unwrap();
+    input.update("pear", ValueCount(3)).unwrap();
+    other_input.update("debt", ValueCount(-2)).unwrap();
+    context.commit().unwrap();
+    assert_eq!(*counts.get(), HashMap::from([(((), 5), ValueCount(1))]));
+    assert_eq!(*other.get(), HashMap::from([("debt", ValueCount(-2))]));
+
+    context.with_frame(|context| {
+        input.remove("pear").unwrap();
+        other_input.update("debt", ValueCount(2)).unwrap();
+        context.commit().unwrap();
+        assert_eq!(*counts.get(), HashMap::from([(((), 4), ValueCount(1))]));
+        assert!(other.get().is_empty());
+    });
+    assert_eq!(*counts.get(), HashMap::from([(((), 5), ValueCount(1))]));
+    assert_eq!(*other.get(), HashMap::from([("debt", ValueCount(-2))]));
+}
+
+#[test]
+fn test_bag_feedback_in_frame() {
+    let mut context = CreationContext::new();
+    let (mut input, rel) = context.bag_input::<usize>();
+    let (derived_input, derived) = context.bag_input::<usize>();
+    context.feedback(rel.map(|x| x + 100), derived_input);
+    let output = context.output(derived);
+    let mut context = context.begin();
+
+    input.send(1).unwrap();
+    context.commit().unwrap();
+    assert_eq!(*output.get(), HashMap::from([(101, ValueCount(1))]));
+
+    context.with_frame(|context| {
+        input.send(1).unwrap();
+        input.send(2).unwrap();
+        context.commit().unwrap();
+        assert_eq!(
+            *output.get(),
+            HashMap::from([(101, ValueCount(2)), (102, ValueCount(1))])
+        );
+    });
+    assert_eq!(*output.get(), HashMap::from([(101, ValueCount(1))]));
+
+    context.commit().unwrap();
+    assert_eq!(*output.get(), HashMap::from([(101, ValueCount(1))]));
+}
@@ -0,0 +1,85 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use standing_relations_2::{CreationContext, ValueCount};
+
+#[test]
+fn test_output_changes() {
+    let mut context = CreationContext::new();
+    let (mut input, relation) = context.input::<char>();
+    let output = context.output(relation);
+    let mut context = context.begin();
+
+    input.send('a').unwrap();
+    context.commit().unwrap();
+    assert_eq!(output.changes(), HashMap::from([('a', ValueCount(1))]));
+    assert!(output.changes().is_empty());
+
+    input.send('b').unwrap();
+    input.remove('a').unwrap();
+    context.commit().unwrap();
+    input.send('c').unwrap();
+    input.remove('c').unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        output.changes(),
+        HashMap::from([('a', ValueCount(-1)), ('b', ValueCount(1))])
+    );
+    assert_eq!(*output.get(), HashMap::from([('b', ValueCount(1))]));
+}
+
+#[test]
+fn test_subscribe() {
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let mut context = CreationContext::new();
+    let (mut input, relation) = context.input::<char>();
+    let sink = received.clone();
+    context.subscribe(relation, move |changes| {
+        sink.borrow_mut().push(changes.clone())
+    });
+    let mut context = context.begin();
+
+    input.send('a').unwrap();
+    input.send('b').unwrap();
+    context.commit().unwrap();
+    context.commit().unwrap();
+    input.remove('a').unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *received.borrow(),
+        vec![
+            HashMap::from([('a', ValueCount(1)), ('b', ValueCount(1))]),
+            HashMap::from([('a', ValueCount(-1))]),
+        ]
+    );
+}
+
+#[test]
+fn test_subscribe_waits_for_fixpoint() {
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let mut context = CreationContext::new();
+    let (mut start_input, start) = context.input::<u32>();
+    let (counter_input, counter) = context.input::<u32>();
+    let counter = counter.save();
+    context.feedback(start, counter_input.clone());
+    context.feedback(
+        counter.get().filter(|&x| x % 10 != 0).map(|x| x + 1),
+        counter_input,
+    );
+    let sink = received.clone();
+    context.subscribe(counter.get(), move |changes| {
+        sink.borrow_mut().push(changes.clone())
+    });
+    let mut context = context.begin();
+
+    start_input.send(7).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *received.borrow(),
+        vec![HashMap::from([
+            (7, ValueCount(1)),
+            (8, ValueCount(1)),
+            (9, ValueCount(1)),
+            (10, ValueCount(1)),
+        ])]
+    );
+}
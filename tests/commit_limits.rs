@@ -0,0 +1,59 @@
+use standing_relations_2::{CommitError, CommitLimits, CreationContext, PipeInfo};
+
+#[test]
+fn test_round_limit_is_resumable() {
+    let mut context = CreationContext::new();
+    let (mut input, start) = context.input::<usize>();
+    let (counter_input, fed_back) = context.input::<usize>();
+    let counter = start.concat(fed_back).save();
+    context.feedback(
+        counter
+            .get()
+            .map(|x| x + 1)
+            .filter(|&x| x <= 25)
+            .named("next"),
+        counter_input,
+    );
+    let output = context.output(counter.get());
+    let mut context = context.begin();
+
+    input.send(0).unwrap();
+    let limits = CommitLimits {
+        max_rounds: Some(10),
+        ..CommitLimits::default()
+    };
+    assert_eq!(
+        context.commit_with_limits(limits),
+        Err(CommitError::RoundLimit {
+            rounds: 10,
+            pipe: PipeInfo {
+                name: Some("next".to_string()),
+                type_name: "filter",
+            },
+        })
+    );
+    assert!(output.get().len() < 26);
+    context.commit().unwrap();
+    assert_eq!(output.get().len(), 26);
+}
+
+#[test]
+fn test_oscillation() {
+    let mut context = CreationContext::new();
+    let (mut start_input, start) = context.input::<char>();
+    let (toggle_input, toggle) = context.bag_input::<char>();
+    let toggle = toggle.save();
+    context.feedback(start.minus(toggle.get()).named("flip"), toggle_input);
+    let mut context = context.begin();
+
+    start_input.send('a').unwrap();
+    let limits = CommitLimits {
+        max_rounds: Some(100),
+        detect_oscillation: true,
+        ..CommitLimits::default()
+    };
+    assert!(matches!(
+        context.commit_with_limits(limits),
+        Err(CommitError::Oscillation { pipe, .. }) if pipe.name.as_deref() == Some("flip")
+    ));
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use standing_relations_2::{CommitError, CreationContext, ValueCount};
+
+#[test]
+fn test_constraint_violation() {
+    let mut context = CreationContext::new();
+    let (mut people_input, people) = context.input::<(u32, &str)>();
+    let (mut pets_input, pets) = context.input::<(&str, u32)>();
+    let people = people.save();
+    let person_name = context.functional_dependency("person_name", people.get());
+    let pet_owner = context.foreign_key("pet_owner", pets.snds(), people.get().fsts());
+    context.constraint("no_bob", people.get().filter(|&(_, name)| name == "Bob"));
+    let mut context = context.begin();
+
+    people_input.send((1, "Alice")).unwrap();
+    pets_input.send(("Rex", 1)).unwrap();
+    context.commit().unwrap();
+
+    people_input.send((1, "Alicia")).unwrap();
+    let Err(CommitError::ConstraintViolation { name, tuples }) = context.commit() else {
+        panic!("expected a constraint violation");
+    };
+    assert_eq!(name, "person_name");
+    let mut tuples = tuples.downcast_ref::<(u32, &str)>().unwrap().clone();
+    tuples.sort();
+    assert_eq!(tuples, vec![(1, "Alice"), (1, "Alicia")]);
+    assert_eq!(person_name.name(), "person_name");
+    assert_eq!(
+        *person_name.get(),
+        HashMap::from([
+            ((1, "Alice"), ValueCount(1)),
+            ((1, "Alicia"), ValueCount(1))
+        ])
+    );
+    people_input.remove((1, "Alicia")).unwrap();
+    context.commit().unwrap();
+    assert!(person_name.get().is_empty());
+
+    pets_input.send(("Tom", 2)).unwrap();
+    let err = context.commit().unwrap_err();
+    assert_eq!(err.to_string(), "constraint pet_owner violated");
+    assert_eq!(*pet_owner.get(), HashMap::from([(2, ValueCount(1))]));
+    people_input.send((2, "Bob")).unwrap();
+    assert!(matches!(
+        context.commit(),
+        Err(CommitError::ConstraintViolation { name, .. }) if name == "no_bob"
+    ));
+    people_input.remove((2, "Bob")).unwrap();
+    people_input.send((2, "Carol")).unwrap();
+    context.commit().unwrap();
+}
+
+#[test]
+fn test_violation_tuples_have_the_constraint_type() {
+    let mut context = CreationContext::new();
+    let (mut input, relation) = context.input::<i32>();
+    context.constraint("positive", relation.filter(|&x| x <= 0));
+    let mut context = context.begin();
+
+    input.send(0).unwrap();
+    let Err(CommitError::ConstraintViolation { tuples, .. }) = context.commit() else {
+        panic!("expected a constraint violation");
+    };
+    assert_eq!(tuples.downcast_ref::<i32>(), Some(&vec![0]));
+    assert_eq!(tuples.downcast_ref::<u32>(), None);
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use standing_relations_2::{CommitId, CreationContext, Op, Relation, RelationInner, ValueCount};
+
+struct Clamp<C> {
+    inner: RelationInner<i32, C>,
+    max: i32,
+}
+
+impl<C: Op<i32>> Op<i32> for Clamp<C> {
+    fn type_name(&self) -> &'static str {
+        "clamp"
+    }
+    fn foreach<F: FnMut(i32, ValueCount)>(&mut self, current_id: CommitId, mut f: F) {
+        let max = self.max;
+        self.inner
+            .foreach(current_id, |x, count| f(x.min(max), count))
+    }
+}
+
+struct Union<CL, CR> {
+    left: RelationInner<i32, CL>,
+    right: RelationInner<i32, CR>,
+}
+
+impl<CL: Op<i32>, CR: Op<i32>> Op<i32> for Union<CL, CR> {
+    fn type_name(&self) -> &'static str {
+        "union"
+    }
+    fn foreach<F: FnMut(i32, ValueCount)>(&mut self, current_id: CommitId, mut f: F) {
+        self.left.foreach(current_id, &mut f);
+        self.right.foreach(current_id, &mut f);
+    }
+}
+
+#[test]
+fn test_custom_ops() {
+    let mut context = CreationContext::new();
+    let (mut left_input, left) = context.input::<i32>();
+    let (mut right_input, right) = context.input::<i32>();
+    let clamped = Relation::from_op(left, |inner| Clamp { inner, max: 10 }).named("clamped");
+    let union = Relation::from_op((clamped, right), |(left, right)| Union { left, right });
+    let output = context.output(union.named("union"));
+    let graph = context.dataflow_graph();
+    assert!(graph
+        .nodes
+        .iter()
+        .any(|node| node.name.as_deref() == Some("clamped") && node.type_name == "clamp"));
+    let mut context = context.begin();
+
+    left_input.send(3).unwrap();
+    left_input.send(12).unwrap();
+    right_input.send(10).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *output.get(),
+        HashMap::from([(3, ValueCount(1)), (10, ValueCount(2))])
+    );
+
+    left_input.remove(12).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *output.get(),
+        HashMap::from([(3, ValueCount(1)), (10, ValueCount(1))])
+    );
+    assert_eq!(context.profile().get("clamped").unwrap().visits, 3);
+}
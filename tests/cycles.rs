@@ -0,0 +1,62 @@
+use standing_relations_2::{CreationContext, CycleCheck, CycleKind};
+
+#[test]
+fn test_monotone_cycle_is_accepted() {
+    let mut context = CreationContext::new();
+    context.set_cycle_check(CycleCheck::Error);
+    let (_edges_input, edges) = context.input::<(u32, u32)>();
+    let edges = edges.save();
+    let paths = context.iterate(edges.get(), |paths| {
+        paths
+            .swaps()
+            .join(edges.get())
+            .map(|(_, from, to)| (from, to))
+            .distinct()
+    });
+    let _output = context.output(paths.get());
+    assert!(context.feedback_cycles().is_empty());
+    assert!(context.try_begin().is_ok());
+}
+
+#[test]
+fn test_non_monotone_cycle_is_reported() {
+    let mut context = CreationContext::new();
+    context.set_cycle_check(CycleCheck::Error);
+    let (_start_input, start) = context.input::<char>();
+    let (toggle_input, toggle) = context.input::<char>();
+    let toggle = toggle.named("toggle").save();
+    let line = line!() + 1;
+    let flipped = start.minus(toggle.get()).named("flip");
+    context.feedback(flipped, toggle_input);
+    let _output = context.output(toggle.get());
+
+    let cycles = context.feedback_cycles();
+    assert_eq!(cycles.len(), 1);
+    let cycle = &cycles[0];
+    assert_eq!(cycle.kind, CycleKind::NonMonotone);
+    assert_eq!(cycle.operator.type_name, "negate");
+    assert_eq!(cycle.operator.location.file(), file!());
+    assert_eq!(cycle.operator.location.line(), line);
+    let names = cycle
+        .relations
+        .iter()
+        .filter_map(|relation| relation.name.as_deref())
+        .collect::<Vec<_>>();
+    assert!(names.contains(&"toggle") && names.contains(&"flip"));
+
+    let err = context.try_begin().err().unwrap();
+    assert!(err.to_string().contains("non-monotone operator negate"));
+}
+
+#[test]
+fn test_warn_does_not_fail_begin() {
+    let mut context = CreationContext::new();
+    context.set_cycle_check(CycleCheck::Warn);
+    let (_start_input, start) = context.input::<char>();
+    let (toggle_input, toggle) = context.input::<char>();
+    let toggle = toggle.save();
+    context.feedback(start.minus(toggle.get()), toggle_input);
+    let _output = context.output(toggle.get());
+    assert_eq!(context.feedback_cycles().len(), 1);
+    assert!(context.try_begin().is_ok());
+}
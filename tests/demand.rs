@@ -0,0 +1,111 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use standing_relations_2::{CreationContext, ValueCount};
+
+#[test]
+fn test_inactive_subscription_catches_up() {
+    let mut context = CreationContext::new();
+    let (mut numbers_input, numbers) = context.input::<u32>();
+    let (squares_input, squares) = context.input::<u32>();
+    let numbers = numbers.save();
+    context.feedback(numbers.get().map(|x| x * x).named("squared"), squares_input);
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let sink = context.subscribe(squares, {
+        let received = received.clone();
+        move |changes| received.borrow_mut().push(changes.clone())
+    });
+    let numbers = context.output(numbers.get());
+    let mut context = context.begin();
+
+    numbers_input.send(2).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *received.borrow(),
+        vec![HashMap::from([(4, ValueCount(1))])]
+    );
+
+    context.set_active(sink, false);
+    assert!(!context.is_active(sink));
+    numbers_input.send(3).unwrap();
+    context.commit().unwrap();
+    numbers_input.remove(2).unwrap();
+    numbers_input.send(4).unwrap();
+    context.commit().unwrap();
+    assert_eq!(received.borrow().len(), 1);
+    assert_eq!(numbers.get().len(), 2);
+    let profile = context.profile();
+    assert_eq!(profile.get("squared").unwrap().last_commit_visits, 0);
+
+    context.set_active(sink, true);
+    context.commit().unwrap();
+    assert_eq!(
+        received.borrow()[1],
+        HashMap::from([(9, ValueCount(1)), (16, ValueCount(1))])
+    );
+}
+
+#[test]
+fn test_feedback_read_by_output_stays_active() {
+    let mut context = CreationContext::new();
+    let (mut numbers_input, numbers) = context.input::<u32>();
+    let (doubles_input, doubles) = context.input::<u32>();
+    context.feedback(numbers.map(|x| x * 2), doubles_input);
+    let doubles = doubles.save();
+    let sink = context.subscribe(doubles.get(), |_| {});
+    let output = context.output(doubles.get());
+    let mut context = context.begin();
+
+    context.set_active(sink, false);
+    numbers_input.send(1).unwrap();
+    context.commit().unwrap();
+    assert_eq!(*output.get(), HashMap::from([(2, ValueCount(1))]));
+}
+
+#[test]
+fn test_catch_up_leaves_sink_suspended() {
+    let mut context = CreationContext::new();
+    let (mut numbers_input, numbers) = context.input::<u32>();
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let sink = context.subscribe(numbers.map(|x| x + 1), {
+        let received = received.clone();
+        move |changes| received.borrow_mut().push(changes.clone())
+    });
+    let mut context = context.begin();
+
+    context.set_active(sink, false);
+    numbers_input.send(1).unwrap();
+    context.commit().unwrap();
+    numbers_input.send(2).unwrap();
+    context.catch_up(sink).unwrap();
+    assert!(!context.is_active(sink));
+    assert_eq!(
+        *received.borrow(),
+        vec![HashMap::from([(2, ValueCount(1)), (3, ValueCount(1))])]
+    );
+
+    numbers_input.send(3).unwrap();
+    context.commit().unwrap();
+    assert_eq!(received.borrow().len(), 1);
+}
+
+#[test]
+fn test_suspended_saved_reader_buffers_net_changes() {
+    let mut context = CreationContext::new();
+    let (mut numbers_input, numbers) = context.input::<u32>();
+    let numbers = numbers.save();
+    let sink = context.subscribe(numbers.get().map(|x| x * x).named("squared"), |_| {});
+    let output = context.output(numbers.get());
+    let mut context = context.begin();
+
+    context.set_active(sink, false);
+    for _ in 0..1000 {
+        numbers_input.send(5).unwrap();
+        context.commit().unwrap();
+        numbers_input.remove(5).unwrap();
+        context.commit().unwrap();
+        output.get();
+    }
+    context.catch_up(sink).unwrap();
+    let profile = context.profile();
+    assert!(profile.get("squared").unwrap().last_commit_visits < 256);
+}
@@ -1,6 +1,6 @@
 use std::{fmt::Debug, hash::Hash};
 
-use standing_relations_2::{CreationContext, SingletonMap};
+use standing_relations_2::{CommitError, CreationContext, SingletonMap};
 
 fn dijkstra<Node: Debug + Ord + Hash + Clone>(
     start: Node,
@@ -24,16 +24,13 @@ fn dijkstra<Node: Debug + Ord + Hash + Clone>(
         .get()
         .semijoin(end_rel)
         .snds()
-        .named("distance_to_end")
-        .collect();
-    let end_distance_output = context.output(distance_to_end.get());
-    context.interrupt(0, distance_to_end.get());
+        .named("distance_to_end");
+    let end_distance = context.interrupt(0, distance_to_end);
 
     let next_distances = distances
         .get()
         .join(edges_rel.map(|(from, to, dist)| (from, (to, dist))))
         .map(|(_, prev_dist, (to, edge_dist))| (to, prev_dist + edge_dist))
-        .collect()
         .antijoin(distances.get().fsts())
         .named("next_distances")
         .collect();
@@ -63,8 +60,8 @@ fn dijkstra<Node: Debug + Ord + Hash + Clone>(
 
     match context.commit() {
         Ok(()) => None,
-        Err(0) => {
-            let m = end_distance_output.get();
+        Err(CommitError::Interrupted(id)) if id == end_distance.id() => {
+            let m = end_distance.get();
             let (&k, v) = m.get_singleton().unwrap();
             eprintln!("{:?}: {:?}", k, v);
             Some(k)
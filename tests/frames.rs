@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use standing_relations_2::{CreationContext, ValueCount};
+
+#[test]
+fn test_rollback_to_unwinds_nested_frames() {
+    let mut context = CreationContext::new();
+    let (mut input, rel) = context.input::<char>();
+    let output = context.output(rel);
+    let mut context = context.begin();
+
+    input.send('a').unwrap();
+    context.commit().unwrap();
+
+    context.push_frame();
+    input.send('b').unwrap();
+    context.commit().unwrap();
+    let inner = context.push_frame();
+    input.send('c').unwrap();
+    context.commit().unwrap();
+    context.push_frame();
+    input.remove('a').unwrap();
+    context.commit().unwrap();
+    assert_eq!(context.frame_depth(), 3);
+    assert_eq!(
+        *output.get(),
+        HashMap::from([('b', ValueCount(1)), ('c', ValueCount(1))])
+    );
+
+    context.rollback_to(inner);
+    assert_eq!(context.frame_depth(), 1);
+    assert_eq!(
+        *output.get(),
+        HashMap::from([('a', ValueCount(1)), ('b', ValueCount(1))])
+    );
+
+    input.send('d').unwrap();
+    context.commit().unwrap();
+    context.pop_frame();
+    assert_eq!(context.frame_depth(), 0);
+    assert_eq!(*output.get(), HashMap::from([('a', ValueCount(1))]));
+}
+
+#[test]
+fn test_frameless_input_survives_rollback() {
+    let mut context = CreationContext::new();
+    let (mut framed_input, framed) = context.input::<char>();
+    let (mut frameless_input, frameless) = context.frameless_input::<char>();
+    assert!(!framed_input.is_frameless());
+    assert!(frameless_input.is_frameless());
+    let framed = context.output(framed);
+    let frameless = context.output(frameless);
+    let mut context = context.begin();
+
+    let frame = context.push_frame();
+    context.push_frame();
+    framed_input.send('a').unwrap();
+    frameless_input.send('a').unwrap();
+    context.commit().unwrap();
+    context.rollback_to(frame);
+
+    assert!(framed.get().is_empty());
+    assert_eq!(*frameless.get(), HashMap::from([('a', ValueCount(1))]));
+}
+
+#[test]
+#[should_panic(expected = "frame already popped")]
+fn test_rollback_to_stale_frame_panics() {
+    let mut context = CreationContext::new();
+    let (_input, rel) = context.input::<char>();
+    let _output = context.output(rel);
+    let mut context = context.begin();
+
+    let stale = context.push_frame();
+    context.pop_frame();
+    let current = context.push_frame();
+    assert_ne!(stale, current);
+    context.rollback_to(stale);
+}
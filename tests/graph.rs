@@ -0,0 +1,38 @@
+use standing_relations_2::{CreationContext, NodeKind};
+
+#[test]
+fn test_dataflow_graph() {
+    let mut context = CreationContext::new();
+    let (_a_input, a) = context.input::<usize>();
+    let (b_input, b) = context.input::<usize>();
+    let a = a.named("a");
+    let b = b.named("b").save();
+    let diff = a.map_h(|x| x + 1).minus(b.get()).named("diff");
+    context.feedback(diff, b_input);
+    let _output = context.output(b.get());
+
+    let graph = context.dataflow_graph();
+    let labels = graph
+        .nodes
+        .iter()
+        .map(|node| (node.name.as_deref(), node.type_name, node.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec![
+            (Some("diff"), "minus", NodeKind::Relation),
+            (Some("a"), "input", NodeKind::Relation),
+            (Some("b"), "input", NodeKind::Relation),
+            (None, "feedback", NodeKind::Sink),
+            (None, "output", NodeKind::Sink),
+        ]
+    );
+    let mut edges = graph.edges.clone();
+    edges.sort();
+    assert_eq!(edges, vec![(0, 3), (1, 0), (2, 0), (2, 4), (3, 2)]);
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph dataflow {\n"));
+    assert!(dot.contains("n0 [label=\"diff\\n(minus)\", shape=ellipse];"));
+    assert!(dot.contains("n3 -> n2;"));
+}
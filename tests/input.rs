@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use standing_relations_2::{CreationContext, ValueCount};
+
+#[test]
+fn test_remove() {
+    let mut context = CreationContext::new();
+    let (mut input, rel) = context.input::<char>();
+    let output = context.output(rel);
+    let mut context = context.begin();
+
+    input.send('a').unwrap();
+    input.send('b').unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *output.get(),
+        HashMap::from([('a', ValueCount(1)), ('b', ValueCount(1))])
+    );
+
+    input.remove('a').unwrap();
+    context.commit().unwrap();
+    assert_eq!(*output.get(), HashMap::from([('b', ValueCount(1))]));
+
+    input.update('c', ValueCount(2)).unwrap();
+    input.update('c', ValueCount(-1)).unwrap();
+    input.update('d', ValueCount(2)).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *output.get(),
+        HashMap::from([('b', ValueCount(1)), ('d', ValueCount(1))])
+    );
+}
+
+#[test]
+fn test_remove_is_idempotent() {
+    let mut context = CreationContext::new();
+    let (mut input, rel) = context.input::<char>();
+    let output = context.output(rel);
+    let mut context = context.begin();
+
+    input.remove('a').unwrap();
+    context.commit().unwrap();
+    input.send('a').unwrap();
+    context.commit().unwrap();
+    assert_eq!(*output.get(), HashMap::from([('a', ValueCount(1))]));
+
+    input.send('a').unwrap();
+    context.commit().unwrap();
+    input.remove('a').unwrap();
+    context.commit().unwrap();
+    assert!(output.get().is_empty());
+
+    context.with_frame(|context| {
+        input.remove('a').unwrap();
+        input.send('b').unwrap();
+        input.send('b').unwrap();
+        context.commit().unwrap();
+    });
+    input.send('b').unwrap();
+    input.remove('b').unwrap();
+    context.commit().unwrap();
+    assert!(output.get().is_empty());
+}
+
+#[test]
+fn test_remove_in_frame() {
+    let mut context = CreationContext::new();
+    let (mut input, rel) = context.input::<char>();
+    let output = context.output(rel);
+    let mut context = context.begin();
+
+    input.send('a').unwrap();
+    input.send('b').unwrap();
+    context.commit().unwrap();
+
+    context.with_frame(|context| {
+        input.remove('a').unwrap();
+        input.send('c').unwrap();
+        context.commit().unwrap();
+        assert_eq!(
+            *output.get(),
+            HashMap::from([('b', ValueCount(1)), ('c', ValueCount(1))])
+        );
+    });
+    assert_eq!(
+        *output.get(),
+        HashMap::from([('a', ValueCount(1)), ('b', ValueCount(1))])
+    );
+}
+
+#[test]
+fn test_frameless_remove() {
+    let mut context = CreationContext::new();
+    let (mut input, rel) = context.frameless_input::<char>();
+    let output = context.output(rel);
+    let mut context = context.begin();
+
+    input.send('a').unwrap();
+    context.commit().unwrap();
+
+    context.with_frame(|context| {
+        input.remove('a').unwrap();
+        context.commit().unwrap();
+    });
+    assert!(output.get().is_empty());
+}
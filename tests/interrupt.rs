@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use standing_relations_2::{CommitError, CreationContext, ValueCount};
+
+#[test]
+fn test_interrupt_handle() {
+    let mut context = CreationContext::new();
+    let (mut input, relation) = context.input::<usize>();
+    let large = context.interrupt(7, relation.filter(|&x| x > 10));
+    let mut context = context.begin();
+
+    input.send(3).unwrap();
+    context.commit().unwrap();
+    assert!(large.get().is_empty());
+
+    input.send(12).unwrap();
+    input.send(15).unwrap();
+    assert_eq!(context.commit(), Err(CommitError::Interrupted(large.id())));
+    assert_eq!(
+        *large.get(),
+        HashMap::from([(12, ValueCount(1)), (15, ValueCount(1))])
+    );
+
+    input.remove(12).unwrap();
+    assert_eq!(context.commit(), Err(CommitError::Interrupted(7)));
+    assert_eq!(*large.get(), HashMap::from([(15, ValueCount(1))]));
+    assert_eq!(*large.delta(), HashMap::from([(12, ValueCount(-1))]));
+}
+
+#[test]
+fn test_interrupt_delta_survives_rollback() {
+    let mut context = CreationContext::new();
+    let (mut input, relation) = context.input::<usize>();
+    let large = context.interrupt(7, relation.filter(|&x| x > 10));
+    let mut context = context.begin();
+
+    context.with_frame(|context| {
+        input.send(12).unwrap();
+        assert_eq!(context.commit(), Err(CommitError::Interrupted(7)));
+    });
+    assert!(large.get().is_empty());
+    assert_eq!(*large.delta(), HashMap::from([(12, ValueCount(1))]));
+
+    input.send(3).unwrap();
+    context.commit().unwrap();
+    assert!(large.delta().is_empty());
+}
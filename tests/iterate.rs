@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+
+use standing_relations_2::CreationContext;
+
+#[test]
+fn test_iterate_transitive_closure() {
+    let mut context = CreationContext::new();
+    let (mut edges_input, edges) = context.input::<(u32, u32)>();
+    let edges = edges.save();
+    let paths = context.iterate(edges.get(), |paths| {
+        paths
+            .swaps()
+            .join(edges.get())
+            .map(|(_, from, to)| (from, to))
+    });
+    let output = context.output(paths.get());
+    let mut context = context.begin();
+
+    edges_input.send((1, 2)).unwrap();
+    edges_input.send((2, 3)).unwrap();
+    context.commit().unwrap();
+    let keys = |output: &standing_relations_2::Output<_, _>| -> HashSet<(u32, u32)> {
+        output.get().keys().copied().collect()
+    };
+    assert_eq!(keys(&output), HashSet::from([(1, 2), (2, 3), (1, 3)]));
+
+    context.with_frame(|context| {
+        edges_input.send((3, 4)).unwrap();
+        context.commit().unwrap();
+        assert_eq!(output.get().len(), 6);
+    });
+    assert_eq!(keys(&output), HashSet::from([(1, 2), (2, 3), (1, 3)]));
+}
+
+#[test]
+fn test_mutually_recursive_variables() {
+    let mut context = CreationContext::new();
+    let (mut edges_input, edges) = context.input::<(u32, u32)>();
+    let edges = edges.save();
+    let (mut start_input, start) = context.input::<u32>();
+    let (even, even_feed) = context.variable::<u32>();
+    let (odd, odd_feed) = context.variable::<u32>();
+    context.feed(
+        odd_feed,
+        even.get()
+            .map(|n| (n, ()))
+            .join(edges.get())
+            .map(|(_, (), to)| to),
+    );
+    context.feed(
+        even_feed,
+        odd.get()
+            .map(|n| (n, ()))
+            .join(edges.get())
+            .map(|(_, (), to)| to)
+            .concat(start),
+    );
+    let even_output = context.output(even.get());
+    let odd_output = context.output(odd.get());
+    let mut context = context.begin();
+
+    start_input.send(0).unwrap();
+    for edge in [(0, 1), (1, 2), (2, 3)] {
+        edges_input.send(edge).unwrap();
+    }
+    context.commit().unwrap();
+    assert_eq!(
+        even_output.get().keys().copied().collect::<HashSet<_>>(),
+        HashSet::from([0, 2])
+    );
+    assert_eq!(
+        odd_output.get().keys().copied().collect::<HashSet<_>>(),
+        HashSet::from([1, 3])
+    );
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use standing_relations_2::{CreationContext, ValueCount};
+
+#[test]
+fn test_left_join() {
+    let mut context = CreationContext::new();
+    let (mut left_input, left) = context.input::<(char, usize)>();
+    let (mut right_input, right) = context.input::<(char, &str)>();
+    let output = context.output(left.left_join(right));
+    let mut context = context.begin();
+
+    left_input.send(('a', 1)).unwrap();
+    left_input.send(('b', 2)).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *output.get(),
+        HashMap::from([
+            (('a', 1, None), ValueCount(1)),
+            (('b', 2, None), ValueCount(1))
+        ])
+    );
+
+    right_input.send(('a', "x")).unwrap();
+    right_input.send(('a', "y")).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *output.get(),
+        HashMap::from([
+            (('a', 1, Some("x")), ValueCount(1)),
+            (('a', 1, Some("y")), ValueCount(1)),
+            (('b', 2, None), ValueCount(1)),
+        ])
+    );
+
+    right_input.remove(('a', "x")).unwrap();
+    context.commit().unwrap();
+    right_input.remove(('a', "y")).unwrap();
+    left_input.send(('a', 3)).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *output.get(),
+        HashMap::from([
+            (('a', 1, None), ValueCount(1)),
+            (('a', 3, None), ValueCount(1)),
+            (('b', 2, None), ValueCount(1)),
+        ])
+    );
+}
+
+#[test]
+fn test_outer_join() {
+    let mut context = CreationContext::new();
+    let (mut left_input, left) = context.input::<(char, usize)>();
+    let (mut right_input, right) = context.input::<(char, &str)>();
+    let output = context.output(left.outer_join(right));
+    let mut context = context.begin();
+
+    right_input.send(('a', "x")).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *output.get(),
+        HashMap::from([(('a', None, Some("x")), ValueCount(1))])
+    );
+
+    left_input.send(('a', 1)).unwrap();
+    left_input.send(('b', 2)).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *output.get(),
+        HashMap::from([
+            (('a', Some(1), Some("x")), ValueCount(1)),
+            (('b', Some(2), None), ValueCount(1)),
+        ])
+    );
+
+    left_input.remove(('a', 1)).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *output.get(),
+        HashMap::from([
+            (('a', None, Some("x")), ValueCount(1)),
+            (('b', Some(2), None), ValueCount(1)),
+        ])
+    );
+}
+
+#[test]
+fn test_antijoin() {
+    let mut context = CreationContext::new();
+    let (mut left_input, left) = context.input::<(char, usize)>();
+    let (mut right_input, right) = context.input::<char>();
+    let output = context.output(left.antijoin(right));
+    let mut context = context.begin();
+
+    left_input.send(('a', 1)).unwrap();
+    left_input.send(('a', 2)).unwrap();
+    left_input.send(('b', 3)).unwrap();
+    right_input.send('b').unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *output.get(),
+        HashMap::from([(('a', 1), ValueCount(1)), (('a', 2), ValueCount(1))])
+    );
+
+    right_input.send('a').unwrap();
+    right_input.remove('b').unwrap();
+    context.commit().unwrap();
+    assert_eq!(*output.get(), HashMap::from([(('b', 3), ValueCount(1))]));
+}
+
+#[test]
+fn test_antijoin_ignores_negative_right_counts() {
+    let mut context = CreationContext::new();
+    let (mut left_input, left) = context.input::<(char, usize)>();
+    let (mut right_input, right) = context.bag_input::<char>();
+    let output = context.output(left.antijoin(right));
+    let mut context = context.begin();
+
+    left_input.send(('a', 1)).unwrap();
+    right_input.update('a', ValueCount(-1)).unwrap();
+    context.commit().unwrap();
+    assert_eq!(*output.get(), HashMap::from([(('a', 1), ValueCount(1))]));
+
+    right_input.update('a', ValueCount(2)).unwrap();
+    context.commit().unwrap();
+    assert!(output.get().is_empty());
+
+    right_input.update('a', ValueCount(-1)).unwrap();
+    context.commit().unwrap();
+    assert_eq!(*output.get(), HashMap::from([(('a', 1), ValueCount(1))]));
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "parallel")]
+
+use standing_relations_2::CreationContext;
+
+#[test]
+fn test_parallel_matches_sequential() {
+    let mut context = CreationContext::new();
+    let (mut left_input, left) = context.input::<(u32, u32)>();
+    let (mut right_input, right) = context.input::<(u32, u32)>();
+    let left = left.save();
+    let right = right.save();
+    let joined = context.output(left.get().par_join(right.get()));
+    let expected_joined = context.output(left.get().join(right.get()));
+    let reduced = context.output(left.get().par_reduce(|_, vals| vals.into_iter().count()));
+    let expected_reduced = context.output(left.get().reduce(|_, vals| vals.into_iter().count()));
+    let mut context = context.begin();
+
+    for round in 0..4 {
+        for i in 0..200 {
+            left_input.send((i % 17, i * round)).unwrap();
+            right_input.send((i % 13, i + round)).unwrap();
+        }
+        for i in (0..200).step_by(3) {
+            left_input.remove((i % 17, i * round)).unwrap();
+        }
+        context.commit().unwrap();
+        assert_eq!(*joined.get(), *expected_joined.get());
+        assert_eq!(*reduced.get(), *expected_reduced.get());
+    }
+    assert!(!joined.get().is_empty());
+}
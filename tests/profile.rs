@@ -0,0 +1,39 @@
+use standing_relations_2::CreationContext;
+
+#[test]
+fn test_profile() {
+    let mut context = CreationContext::new();
+    let (mut input, rel) = context.input::<usize>();
+    let doubled = rel.named("numbers").map(|x| x * 2).named("doubled");
+    let output = context.output(doubled);
+    let mut context = context.begin();
+    context.set_profile_timing(true);
+
+    for x in 0..3 {
+        input.send(x).unwrap();
+    }
+    context.commit().unwrap();
+    assert_eq!(output.get().len(), 3);
+
+    input.send(3).unwrap();
+    context.commit().unwrap();
+    assert_eq!(output.get().len(), 4);
+
+    let profile = context.profile();
+    let numbers = profile.get("numbers").unwrap();
+    assert_eq!(numbers.type_name, "input");
+    assert_eq!(numbers.visits, 4);
+    assert_eq!(numbers.last_commit_visits, 1);
+    let doubled = profile.get("doubled").unwrap();
+    assert_eq!(doubled.type_name, "map");
+    assert_eq!(doubled.visits, 4);
+    assert_eq!(doubled.last_commit_visits, 1);
+    assert!(doubled.last_commit_elapsed <= doubled.elapsed);
+
+    context.reset_profile();
+    let profile = context.profile();
+    assert!(profile
+        .relations
+        .iter()
+        .all(|relation| relation.visits == 0 && relation.elapsed.is_zero()));
+}
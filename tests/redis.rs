@@ -0,0 +1,186 @@
+#![cfg(feature = "redis")]
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use redis::Commands;
+use standing_relations_2::{CommitError, CreationContext, RedisEncoding, RedisLayout, RedisRetry};
+
+#[test]
+fn test_unreachable_redis_fails_commit() {
+    let client = redis::Client::open("redis://127.0.0.1:1/").unwrap();
+    let mut context = CreationContext::with_redis(client);
+    context.set_redis_retry(RedisRetry {
+        retries: 1,
+        delay: Duration::ZERO,
+    });
+    let (mut input, relation) = context.input::<u32>();
+    context.send_to_redis("unreachable", relation);
+    let mut context = context.begin();
+
+    input.send(1).unwrap();
+    assert!(matches!(
+        context.commit(),
+        Err(CommitError::SinkFailed { .. })
+    ));
+    assert!(matches!(
+        context.commit(),
+        Err(CommitError::SinkFailed { .. })
+    ));
+}
+
+#[test]
+fn test_unreachable_redis_fails_close() {
+    let client = redis::Client::open("redis://127.0.0.1:1/").unwrap();
+    let mut context = CreationContext::with_redis(client);
+    context.set_redis_retry(RedisRetry {
+        retries: 1,
+        delay: Duration::ZERO,
+    });
+    let (mut input, relation) = context.input::<u32>();
+    context.send_to_redis("unreachable", relation);
+    let mut context = context.begin();
+
+    input.send(1).unwrap();
+    assert!(context.commit().is_err());
+    assert!(matches!(
+        context.close(),
+        Err(CommitError::SinkFailed { .. })
+    ));
+}
+
+#[test]
+fn test_encoding_error_fails_commit() {
+    let client = redis::Client::open("redis://127.0.0.1:1/").unwrap();
+    let mut context = CreationContext::with_redis(client);
+    let (mut input, relation) = context.input::<u32>();
+    context.send_to_redis_with(
+        "unencodable",
+        relation,
+        RedisEncoding::new(|_| Err("no encoding".into()), |_, _| Ok(Vec::new())),
+    );
+    let mut context = context.begin();
+
+    input.send(1).unwrap();
+    assert!(matches!(
+        context.commit(),
+        Err(CommitError::SinkFailed { message, .. }) if message == "no encoding"
+    ));
+}
+
+#[test]
+#[ignore = "needs a local redis-server"]
+fn test_send_to_redis() {
+    let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+    let mut connection = client.get_connection().unwrap();
+    let mut context = CreationContext::with_redis(client);
+    let (mut input, relation) = context.input::<u32>();
+    context.send_to_redis("test_send_to_redis", relation);
+    let mut context = context.begin();
+
+    input.send(1).unwrap();
+    input.send(2).unwrap();
+    context.commit().unwrap();
+    let values: Vec<Option<isize>> = connection
+        .mget(&["test_send_to_redis:1", "test_send_to_redis:2"])
+        .unwrap();
+    assert_eq!(values, vec![Some(1), Some(1)]);
+
+    input.remove(1).unwrap();
+    context.commit().unwrap();
+    let exists: bool = connection.exists("test_send_to_redis:1").unwrap();
+    assert!(!exists);
+
+    context.close().unwrap();
+    let exists: bool = connection.exists("test_send_to_redis:2").unwrap();
+    assert!(!exists);
+}
+
+#[test]
+#[ignore = "needs a local redis-server"]
+fn test_send_to_redis_split() {
+    let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+    let mut connection = client.get_connection().unwrap();
+    let mut context = CreationContext::with_redis(client);
+    let (mut input, relation) = context.input::<(String, u32)>();
+    context.send_to_redis_with(
+        "test_send_to_redis_split",
+        relation,
+        RedisEncoding::split(
+            |k: &String| Ok(k.clone().into_bytes()),
+            |v: &u32| Ok(v.to_string().into_bytes()),
+        ),
+    );
+    let mut context = context.begin();
+
+    input.send(("a".to_string(), 1)).unwrap();
+    input.send(("b".to_string(), 2)).unwrap();
+    context.commit().unwrap();
+    input.remove(("a".to_string(), 1)).unwrap();
+    input.send(("a".to_string(), 3)).unwrap();
+    context.commit().unwrap();
+    let values: Vec<Option<u32>> = connection
+        .mget(&["test_send_to_redis_split:a", "test_send_to_redis_split:b"])
+        .unwrap();
+    assert_eq!(values, vec![Some(3), Some(2)]);
+
+    input.remove(("b".to_string(), 2)).unwrap();
+    context.commit().unwrap();
+    let exists: bool = connection.exists("test_send_to_redis_split:b").unwrap();
+    assert!(!exists);
+}
+
+#[test]
+#[ignore = "needs a local redis-server"]
+fn test_send_to_redis_layouts() {
+    let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+    let mut connection = client.get_connection().unwrap();
+    let mut context = CreationContext::with_redis(client);
+    let (mut input, relation) = context.input::<(String, u32)>();
+    let relation = relation.save();
+    let split = || {
+        RedisEncoding::split(
+            |k: &String| Ok(k.clone().into_bytes()),
+            |v: &u32| Ok(v.to_string().into_bytes()),
+        )
+    };
+    context.send_to_redis_as(
+        "test_layout_hash",
+        relation.get(),
+        RedisLayout::Hash,
+        split(),
+    );
+    context.send_to_redis_as("test_layout_set", relation.get(), RedisLayout::Set, split());
+    context.send_to_redis_as(
+        "test_layout_zset",
+        relation.get(),
+        RedisLayout::sorted_set(|&(_, v)| v as f64),
+        split(),
+    );
+    let mut context = context.begin();
+
+    input.send(("a".to_string(), 3)).unwrap();
+    input.send(("b".to_string(), 1)).unwrap();
+    input.send(("c".to_string(), 2)).unwrap();
+    context.commit().unwrap();
+    input.remove(("c".to_string(), 2)).unwrap();
+    context.commit().unwrap();
+
+    let hash: HashMap<String, u32> = connection.hgetall("test_layout_hash").unwrap();
+    assert_eq!(
+        hash,
+        HashMap::from([("a".to_string(), 3), ("b".to_string(), 1)])
+    );
+    let set: HashSet<String> = connection.smembers("test_layout_set").unwrap();
+    assert_eq!(set, HashSet::from(["a".to_string(), "b".to_string()]));
+    let zset: Vec<String> = connection.zrange("test_layout_zset", 0, -1).unwrap();
+    assert_eq!(zset, vec!["b".to_string(), "a".to_string()]);
+
+    drop(context);
+    let exists: usize = connection
+        .exists(&["test_layout_hash", "test_layout_set", "test_layout_zset"])
+        .unwrap();
+    assert_eq!(exists, 0);
+}
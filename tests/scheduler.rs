@@ -0,0 +1,93 @@
+use std::{cell::Cell, rc::Rc};
+
+use standing_relations_2::{
+    CommitId, CreationContext, ExecutionContext, Input, Op, Output, Relation, RelationInner,
+    Scheduler, ValueCount,
+};
+
+// Counts how often the relation is pulled, whether or not anything changed.
+struct Polls<C> {
+    inner: RelationInner<u32, C>,
+    polls: Rc<Cell<usize>>,
+}
+
+impl<C: Op<u32>> Op<u32> for Polls<C> {
+    fn type_name(&self) -> &'static str {
+        "polls"
+    }
+    fn foreach<F: FnMut(u32, ValueCount)>(&mut self, current_id: CommitId, f: F) {
+        self.polls.set(self.polls.get() + 1);
+        self.inner.foreach(current_id, f)
+    }
+}
+
+struct Program<'a> {
+    context: ExecutionContext<'a>,
+    start_input: Input<u32>,
+    other_input: Input<u32>,
+    counter: Output<u32>,
+    copied: Output<u32>,
+    other_polls: Rc<Cell<usize>>,
+}
+
+fn program<'a>() -> Program<'a> {
+    let mut context = CreationContext::new();
+    let (mut start_input, start) = context.input::<u32>();
+    let (other_input, other) = context.input::<u32>();
+    let (counter_input, counter) = context.input::<u32>();
+    let (copied_input, copied) = context.input::<u32>();
+    let counter = counter.save();
+    context.feedback(start, counter_input.clone());
+    let other_polls = Rc::new(Cell::new(0));
+    let polled = Relation::from_op(other.map(|x| x + 100), |inner| Polls {
+        inner,
+        polls: other_polls.clone(),
+    });
+    context.feedback(polled.dynamic(), copied_input);
+    context.feedback(
+        counter.get().filter(|&x| x < 10).map(|x| x + 1),
+        counter_input,
+    );
+    let counter = context.output(counter.get().dynamic());
+    let copied = context.output(copied.dynamic());
+    start_input.send(0).unwrap();
+    Program {
+        context: context.begin(),
+        start_input,
+        other_input,
+        counter,
+        copied,
+        other_polls,
+    }
+}
+
+#[test]
+fn test_independent_pipes_are_not_repolled() {
+    let mut program = program();
+    program.other_input.send(1).unwrap();
+    program.context.commit().unwrap();
+    assert_eq!(program.counter.get().len(), 11);
+    assert_eq!(program.copied.get().len(), 1);
+    assert_eq!(program.other_polls.get(), 1);
+
+    program.start_input.send(5).unwrap();
+    program.context.commit().unwrap();
+    assert_eq!(program.other_polls.get(), 2);
+}
+
+#[test]
+fn test_schedulers_agree() {
+    let mut priority = program();
+    let mut fifo = program();
+    fifo.context.set_scheduler(Scheduler::Fifo);
+    for run in [&mut priority, &mut fifo] {
+        run.other_input.send(5).unwrap();
+        run.context.commit().unwrap();
+        run.start_input.send(3).unwrap();
+        run.other_input.send(6).unwrap();
+        run.context.commit().unwrap();
+    }
+    assert_eq!(*priority.counter.get(), *fifo.counter.get());
+    assert_eq!(*priority.copied.get(), *fifo.copied.get());
+    assert_eq!(fifo.copied.get().len(), 2);
+}
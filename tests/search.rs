@@ -0,0 +1,144 @@
+use std::collections::{BTreeSet, HashMap};
+
+use standing_relations_2::{
+    search::{Ascending, FewestOptions, Search, SearchError},
+    CreationContext, ValueCount,
+};
+
+fn queens(n: u8, fewest_options: bool) -> Vec<BTreeSet<(u8, u8)>> {
+    let mut context = CreationContext::new();
+    let (mut cells_input, cells) = context.input::<(u8, u8)>();
+    let (queens_input, queens) = context.input::<(u8, u8)>();
+    let cells = cells.save();
+    let queens = queens.save();
+
+    let attacks = queens
+        .get()
+        .map(|q| ((), q))
+        .join(queens.get().map(|q| ((), q)))
+        .filter(|&((), (r1, c1), (r2, c2))| {
+            (r1, c1) < (r2, c2) && (r1 == r2 || c1 == c2 || r1.abs_diff(r2) == c1.abs_diff(c2))
+        });
+    let conflict = context.interrupt(0, attacks);
+
+    let full = queens
+        .get()
+        .map(|_| ())
+        .counts()
+        .filter(move |&((), count)| count == n as isize)
+        .map(|_| ());
+    let solved = context.interrupt(1, queens.get().map(|q| ((), q)).semijoin(full).snds());
+
+    let free_cells = cells.get().antijoin(queens.get().fsts());
+    let candidates = if fewest_options {
+        context.output(free_cells.dynamic())
+    } else {
+        let next_row = cells
+            .get()
+            .fsts()
+            .distinct()
+            .minus(queens.get().fsts())
+            .global_min();
+        context.output(free_cells.semijoin(next_row).dynamic())
+    };
+    let placed = context.output(queens.get());
+    let mut context = context.begin();
+
+    for r in 0..n {
+        for c in 0..n {
+            cells_input.send((r, c)).unwrap();
+        }
+    }
+    context.commit().unwrap();
+
+    let search = Search::new(
+        &mut context,
+        &candidates,
+        queens_input,
+        conflict.id(),
+        &solved,
+    )
+    .unwrap();
+    let search = if fewest_options {
+        search.order(FewestOptions)
+    } else {
+        search.order(Ascending)
+    };
+    let solutions = search
+        .map(|solution| solution.unwrap().into_keys().collect())
+        .collect();
+    assert!(placed.get().is_empty());
+    solutions
+}
+
+#[test]
+fn test_search_finds_all_solutions() {
+    let solutions = queens(4, false);
+    assert_eq!(
+        solutions,
+        vec![
+            BTreeSet::from([(0, 1), (1, 3), (2, 0), (3, 2)]),
+            BTreeSet::from([(0, 2), (1, 0), (2, 3), (3, 1)]),
+        ]
+    );
+    assert_eq!(queens(6, false).len(), 4);
+}
+
+#[test]
+fn test_search_fewest_options() {
+    assert_eq!(queens(4, true), queens(4, false));
+    assert_eq!(queens(6, true).len(), 4);
+}
+
+#[test]
+fn test_search_stops_early() {
+    let mut context = CreationContext::new();
+    let (choose_input, chosen) = context.input::<u32>();
+    let (mut options_input, options) = context.input::<u32>();
+    let chosen = chosen.save();
+    let conflict = context.interrupt(0, chosen.get().filter(|&x| x % 2 == 1));
+    let solved = context.interrupt(1, chosen.get());
+    let candidates = context.output(options.dynamic());
+    let values = context.output(chosen.get());
+    let mut context = context.begin();
+
+    for x in 1..=4 {
+        options_input.send(x).unwrap();
+    }
+    let mut search = Search::new(
+        &mut context,
+        &candidates,
+        choose_input,
+        conflict.id(),
+        &solved,
+    )
+    .unwrap()
+    .order(Ascending);
+    assert_eq!(search.next(), Some(Ok(HashMap::from([(2, ValueCount(1))]))));
+    drop(search);
+    assert!(values.get().is_empty());
+}
+
+#[test]
+fn test_search_rejects_foreign_choice_input() {
+    let mut other = CreationContext::new();
+    let (foreign_input, foreign) = other.input::<u32>();
+    let _foreign = other.output(foreign);
+
+    let mut context = CreationContext::new();
+    let (_choose_input, chosen) = context.input::<u32>();
+    let chosen = chosen.save();
+    let conflict = context.interrupt(0, chosen.get().filter(|&x| x % 2 == 1));
+    let solved = context.interrupt(1, chosen.get());
+    let candidates = context.output(chosen.get().dynamic());
+    let mut context = context.begin();
+
+    let search = Search::new(
+        &mut context,
+        &candidates,
+        foreign_input,
+        conflict.id(),
+        &solved,
+    );
+    assert_eq!(search.err(), Some(SearchError::ForeignInput));
+}
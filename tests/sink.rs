@@ -0,0 +1,113 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use standing_relations_2::{CommitError, CreationContext, Sink, SinkError, ValueCount};
+
+struct Bus {
+    published: Rc<RefCell<Vec<HashMap<u32, ValueCount>>>>,
+    fail: Rc<RefCell<bool>>,
+}
+
+impl Sink<u32> for Bus {
+    fn receive(&mut self, changes: &HashMap<u32, ValueCount>) -> Result<(), SinkError> {
+        if *self.fail.borrow() {
+            return Err(SinkError::Failed("bus unavailable".to_string()));
+        }
+        if changes.contains_key(&0) {
+            return Err(SinkError::Interrupt(7));
+        }
+        self.published.borrow_mut().push(changes.clone());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sink_receives_consolidated_changes() {
+    let published = Rc::new(RefCell::new(Vec::new()));
+    let fail = Rc::new(RefCell::new(false));
+    let mut context = CreationContext::new();
+    let (mut start_input, start) = context.input::<u32>();
+    let (counter_input, counter) = context.input::<u32>();
+    let counter = counter.named("counter").save();
+    context.feedback(start, counter_input.clone());
+    context.feedback(
+        counter.get().filter(|&x| x % 10 != 0).map(|x| x + 1),
+        counter_input,
+    );
+    context.sink(
+        counter.get(),
+        Bus {
+            published: published.clone(),
+            fail: fail.clone(),
+        },
+    );
+    let mut context = context.begin();
+
+    start_input.send(7).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        *published.borrow(),
+        vec![HashMap::from([
+            (7, ValueCount(1)),
+            (8, ValueCount(1)),
+            (9, ValueCount(1)),
+            (10, ValueCount(1)),
+        ])]
+    );
+
+    *fail.borrow_mut() = true;
+    start_input.send(20).unwrap();
+    let err = context.commit().unwrap_err();
+    assert!(matches!(
+        &err,
+        CommitError::SinkFailed { pipe, message }
+            if pipe.name.as_deref() == Some("counter") && message == "bus unavailable"
+    ));
+
+    *fail.borrow_mut() = false;
+    start_input.send(30).unwrap();
+    context.commit().unwrap();
+    assert_eq!(
+        published.borrow()[1],
+        HashMap::from([(20, ValueCount(1)), (30, ValueCount(1))])
+    );
+
+    start_input.send(0).unwrap();
+    assert_eq!(context.commit(), Err(CommitError::Interrupted(7)));
+    context.commit().unwrap();
+    assert_eq!(published.borrow().len(), 2);
+}
+
+struct Failing;
+
+impl Sink<u32> for Failing {
+    fn receive(&mut self, _changes: &HashMap<u32, ValueCount>) -> Result<(), SinkError> {
+        Err(SinkError::Failed("down".to_string()))
+    }
+}
+
+#[test]
+fn test_failed_sink_does_not_block_later_sinks() {
+    let published = Rc::new(RefCell::new(Vec::new()));
+    let mut context = CreationContext::new();
+    let (mut input, relation) = context.input::<u32>();
+    let relation = relation.named("numbers").save();
+    context.sink(relation.get(), Failing);
+    context.sink(
+        relation.get(),
+        Bus {
+            published: published.clone(),
+            fail: Rc::new(RefCell::new(false)),
+        },
+    );
+    let mut context = context.begin();
+
+    input.send(1).unwrap();
+    assert!(matches!(
+        context.commit(),
+        Err(CommitError::SinkFailed { message, .. }) if message == "down"
+    ));
+    assert_eq!(
+        *published.borrow(),
+        vec![HashMap::from([(1, ValueCount(1))])]
+    );
+}
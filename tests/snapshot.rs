@@ -0,0 +1,95 @@
+#![cfg(feature = "serde")]
+
+use standing_relations_2::{
+    CreationContext, ExecutionContext, Input, Output, Relation, SnapshotError,
+};
+
+struct Reachability<'a> {
+    context: ExecutionContext<'a>,
+    edges_input: Input<(u32, u32)>,
+    start_input: Input<u32>,
+    reached: Output<u32>,
+}
+
+fn reachability<'a>() -> Reachability<'a> {
+    let mut context = CreationContext::new();
+    let (start_input, start) = context.persisted_input::<u32>();
+    let (edges_input, edges) = context.persisted_input::<(u32, u32)>();
+    let (reached_input, reached) = context.persisted_input::<u32>();
+    let reached = reached.named("reached").save();
+    context.feedback(start, reached_input.clone());
+    context.feedback(
+        reached
+            .get()
+            .map(|n| (n, ()))
+            .join(edges)
+            .map(|(_, (), to)| to),
+        reached_input,
+    );
+    let reached: Relation<u32> = reached.get().dynamic();
+    let reached = context.output(reached);
+    Reachability {
+        context: context.begin(),
+        edges_input,
+        start_input,
+        reached,
+    }
+}
+
+#[test]
+fn test_restore_matches_original() {
+    let mut original = reachability();
+    original.start_input.send(1).unwrap();
+    for edge in [(1, 2), (2, 3), (4, 5)] {
+        original.edges_input.send(edge).unwrap();
+    }
+    original.context.commit().unwrap();
+
+    let snapshot = original.context.snapshot().unwrap();
+    let snapshot = serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+
+    let mut restored = reachability();
+    restored.context.restore(&snapshot).unwrap();
+    restored.context.commit().unwrap();
+    assert_eq!(*restored.reached.get(), *original.reached.get());
+
+    for run in [&mut original, &mut restored] {
+        run.edges_input.send((3, 4)).unwrap();
+        run.context.commit().unwrap();
+    }
+    assert_eq!(restored.reached.get().len(), 5);
+    assert_eq!(*restored.reached.get(), *original.reached.get());
+}
+
+#[test]
+fn test_restore_rejects_other_graph() {
+    let mut original = reachability();
+    original.start_input.send(1).unwrap();
+    original.context.commit().unwrap();
+    let snapshot = original.context.snapshot().unwrap();
+
+    let mut context = CreationContext::new();
+    let (_input, relation) = context.persisted_input::<u32>();
+    let _output = context.output(relation.named("other"));
+    let mut context = context.begin();
+    assert!(matches!(
+        context.restore(&snapshot),
+        Err(SnapshotError::GraphMismatch)
+    ));
+}
+
+#[test]
+fn test_snapshot_rejects_unpersisted_input() {
+    let mut context = CreationContext::new();
+    let (_persisted, persisted) = context.persisted_input::<u32>();
+    let (mut input, relation) = context.input::<u32>();
+    let output = context.output(persisted.concat(relation));
+    let mut context = context.begin();
+    input.send(1).unwrap();
+    context.commit().unwrap();
+    assert_eq!(output.get().len(), 1);
+    assert!(matches!(
+        context.snapshot(),
+        Err(SnapshotError::NotPersisted)
+    ));
+}
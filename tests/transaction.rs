@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use standing_relations_2::{CreationContext, TransactionError, ValueCount};
+
+#[test]
+fn test_transaction_rolls_back_violation() {
+    let mut context = CreationContext::new();
+    let (mut start_input, start) = context.input::<u32>();
+    let (mut edges_input, edges) = context.input::<(u32, u32)>();
+    let (reached_input, reached) = context.input::<u32>();
+    let reached = reached.save();
+    context.feedback(start, reached_input.clone());
+    context.feedback(
+        reached
+            .get()
+            .map(|n| (n, ()))
+            .join(edges)
+            .map(|(_, (), to)| to),
+        reached_input,
+    );
+    let forbidden = context.interrupt(0, reached.get().filter(|&n| n == 0));
+    let reached = context.output(reached.get());
+    let mut context = context.begin();
+
+    let result = context.transaction(&forbidden, |_| {
+        start_input.send(1).unwrap();
+        edges_input.send((1, 2)).unwrap();
+    });
+    assert_eq!(result, Ok(()));
+    let before = reached.get().clone();
+    assert_eq!(before.len(), 2);
+
+    let result = context.transaction(&forbidden, |context| {
+        edges_input.send((2, 3)).unwrap();
+        context.commit().unwrap();
+        edges_input.send((3, 0)).unwrap();
+        edges_input.send((0, 4)).unwrap();
+    });
+    assert_eq!(
+        result,
+        Err(TransactionError::Violation(HashMap::from([(
+            0,
+            ValueCount(1)
+        )])))
+    );
+    assert_eq!(*reached.get(), before);
+    assert_eq!(context.frame_depth(), 0);
+
+    edges_input.send((2, 5)).unwrap();
+    context.commit().unwrap();
+    assert_eq!(reached.get().len(), 3);
+}
+
+#[test]
+fn test_nested_transaction_merges_into_frame() {
+    let mut context = CreationContext::new();
+    let (mut input, relation) = context.input::<u32>();
+    let relation = relation.save();
+    let violation = context.interrupt(0, relation.get().filter(|&x| x > 10));
+    let output = context.output(relation.get());
+    let mut context = context.begin();
+
+    input.send(1).unwrap();
+    context.commit().unwrap();
+
+    context.with_frame(|context| {
+        let result = context.transaction(&violation, |context| {
+            input.send(2).unwrap();
+            context.transaction(&violation, |_| {
+                input.send(3).unwrap();
+            })
+        });
+        assert_eq!(result, Ok(Ok(())));
+        let result = context.transaction(&violation, |_| {
+            input.remove(1).unwrap();
+            input.send(11).unwrap();
+        });
+        assert!(matches!(result, Err(TransactionError::Violation(_))));
+        assert_eq!(
+            *output.get(),
+            HashMap::from([(1, ValueCount(1)), (2, ValueCount(1)), (3, ValueCount(1))])
+        );
+    });
+    assert_eq!(*output.get(), HashMap::from([(1, ValueCount(1))]));
+}
+
+#[test]
+fn test_transaction_keeps_frameless_sends() {
+    let mut context = CreationContext::new();
+    let (mut input, relation) = context.input::<u32>();
+    let (mut log_input, log) = context.frameless_input::<u32>();
+    let violation = context.interrupt(0, relation.filter(|&x| x > 10));
+    let log = context.output(log);
+    let mut context = context.begin();
+
+    let result = context.transaction(&violation, |_| {
+        log_input.send(1).unwrap();
+        input.send(11).unwrap();
+    });
+    assert!(matches!(result, Err(TransactionError::Violation(_))));
+    assert_eq!(*log.get(), HashMap::from([(1, ValueCount(1))]));
+}